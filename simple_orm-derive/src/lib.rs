@@ -1,119 +1,705 @@
+#![allow(clippy::needless_return)]
+
 use proc_macro::{self, TokenStream};
+use proc_macro2::Span;
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{
+    parenthesized, parse_macro_input, punctuated::Punctuated, spanned::Spanned, Attribute, Data,
+    DeriveInput, Field, GenericArgument, Ident, Lit, LitStr, Path, PathArguments, Token, Type,
+};
 
-#[proc_macro_derive(DatabaseInsertable, attributes(simple_orm))]
-pub fn derive(input: TokenStream) -> TokenStream {
-    let DeriveInput { ident, data, .. } = parse_macro_input!(input);
-
-    return match data {
-        Data::Struct(data_values) => {
-            // Generate code for `fields_value` function
-            let fields_value_fn = {
-                let field_names = data_values.fields.iter().map(|field| {
-                    let mut is_primary_key = false;
-                    for attr in &field.attrs {
-                        let attrs_str = attr.meta.to_token_stream().to_string();
-                        let simple_orm_attrs = attrs_str.split("(").collect::<Vec<&str>>();
-                        if simple_orm_attrs[0] == "simple_orm" && simple_orm_attrs.len() > 1 {
-                            let attrs = simple_orm_attrs[1].split(" ").collect::<Vec<&str>>();
-                            for attr in attrs {
-                                if attr == "primary_key" || attr == "primary_key)" {
-                                    is_primary_key = true;
-                                }
-                            }
-                        }
-                    }
-                    (&field.ident, is_primary_key)
-                });
-                let field_value_initializers = field_names.clone().map(|(name, is_primary)| {
-                    let mut is_primary_quote = quote! {};
-                    if is_primary {
-                        is_primary_quote = quote! {
-                            .is_primary_key()
-                        }
-                    }
-                    quote! { DatabaseField::builder(stringify!(#name), FieldType::from(self.#name.clone()))#is_primary_quote.build() }
-                });
-
-                quote! {
-                    fn fields_value(&self) -> Vec<DatabaseField> {
-                        return vec![
-                            #( #field_value_initializers ),*
-                        ];
-                    }
+/// Postgres reserved keywords, which cannot be used as table or column name without quoting.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+/// Converts a `CamelCase` struct name into `snake_case`.
+fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<char>>();
+    let mut snake = String::new();
+    for (idx, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_is_lower = chars.get(idx + 1).is_some_and(|n| n.is_lowercase());
+            if prev != '_' && (!prev.is_uppercase() || next_is_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    return snake;
+}
+
+/// Naive english plural, good enough for table names.
+fn pluralize(name: &str) -> String {
+    let ends_with_consonant_y =
+        name.ends_with('y') && !name[..name.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+    if ends_with_consonant_y {
+        return format!("{}ies", &name[..name.len() - 1]);
+    }
+    if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|end| name.ends_with(end))
+    {
+        return format!("{}es", name);
+    }
+    return format!("{}s", name);
+}
+
+/// Checks that `name` is a valid SQL identifier, optionally schema qualified.
+/// Reserved keywords are only rejected for columns, which conditions refer to
+/// by name, tables being quoted by [`quote_reserved`] instead.
+fn validate_identifier(name: &str, kind: &str, allow_schema: bool) -> Result<(), String> {
+    let parts = match allow_schema {
+        true => name.split('.').collect::<Vec<&str>>(),
+        false => vec![name],
+    };
+    for part in parts {
+        let mut chars = part.chars();
+        let valid_start = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_');
+        if !valid_start || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!(
+                "\"{}\" is not a valid {} name, only ASCII letters, digits and '_' are allowed and it cannot start with a digit",
+                name, kind
+            ));
+        }
+        if kind == "column" && RESERVED_KEYWORDS.contains(&part.to_lowercase().as_str()) {
+            return Err(format!(
+                "\"{}\" is a reserved SQL keyword, set another name with #[simple_orm(column = \"...\")]",
+                part
+            ));
+        }
+    }
+    return Ok(());
+}
+
+/// Quotes the parts of `name` which are reserved keywords, in lower case so
+/// that they name the table Postgres would fold the unquoted name to.
+fn quote_reserved(name: &str) -> String {
+    return name
+        .split('.')
+        .map(|part| {
+            let lower = part.to_lowercase();
+            match RESERVED_KEYWORDS.contains(&lower.as_str()) {
+                true => format!("\"{}\"", lower),
+                false => part.to_owned(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(".");
+}
+
+/// Struct level `#[simple_orm(...)]` attributes.
+struct StructAttributes {
+    table_name: String,
+    /// Path to the `simple_orm` crate in generated code.
+    krate: Path,
+}
+
+/// Parses the struct level `#[simple_orm(table = "...", pluralize, crate = "...")]` attributes,
+/// the table name defaulting to the struct name in snake case.
+fn parse_struct_attributes(ident: &Ident, attrs: &[Attribute]) -> syn::Result<StructAttributes> {
+    let mut table: Option<LitStr> = None;
+    let mut plural = false;
+    let mut krate: Option<Path> = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("simple_orm")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse()?);
+                return Ok(());
+            }
+            if meta.path.is_ident("pluralize") {
+                plural = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("crate") {
+                let path: LitStr = meta.value()?.parse()?;
+                krate = Some(path.parse()?);
+                return Ok(());
+            }
+            return Err(meta.error(
+                "unsupported simple_orm attribute, expected `table`, `pluralize` or `crate`",
+            ));
+        })?;
+    }
+    let table_name = match table {
+        Some(table) => {
+            let name = table.value();
+            validate_identifier(&name, "table", true)
+                .map_err(|e| syn::Error::new(table.span(), e))?;
+            name
+        }
+        None => {
+            let mut name = to_snake_case(&ident.to_string());
+            if plural {
+                name = pluralize(&name);
+            }
+            validate_identifier(&name, "table", true)
+                .map_err(|e| syn::Error::new(ident.span(), e))?;
+            name
+        }
+    };
+    return Ok(StructAttributes {
+        table_name: quote_reserved(&table_name),
+        krate: krate.unwrap_or_else(|| syn::parse_quote!(::simple_orm)),
+    });
+}
+
+/// Returns `T` if `ty` is an `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// Generates the expression converting the `FieldType` bound to `value` into `ty`.
+fn field_type_conversion(
+    krate: &Path,
+    name: &Ident,
+    ty: &Type,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let field_type = quote! { #krate::models::database_field::FieldType };
+    if let Some(inner) = option_inner_type(ty) {
+        let inner_conversion = field_type_conversion(krate, name, inner)?;
+        return Ok(quote! {
+            match value {
+                #field_type::Null(_) => None,
+                value => Some(#inner_conversion),
+            }
+        });
+    }
+    let ty_str = ty.to_token_stream().to_string();
+    let mismatch = quote! {
+        _ => return Err(format!("Mismatched field type for '{}'", stringify!(#name))),
+    };
+    let conversion = if ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64"]
+        .contains(&ty_str.as_str())
+    {
+        let out_of_range = quote! {
+            return Err(format!("Value out of range for '{}'", stringify!(#name)))
+        };
+        quote! {
+            match value.integer_value().map(|val| val.try_into()) {
+                Some(Ok(val)) => val,
+                Some(Err(_)) => #out_of_range,
+                None => return Err(format!("Mismatched field type for '{}'", stringify!(#name))),
+            }
+        }
+    } else if ty_str == "f32" {
+        quote! {
+            match value {
+                #field_type::Real(val) => val,
+                #mismatch
+            }
+        }
+    } else if ty_str == "f64" {
+        quote! {
+            match value {
+                #field_type::Real(val) => val.into(),
+                #field_type::Double(val) => val,
+                #mismatch
+            }
+        }
+    } else if ty_str == "&str" || ty_str == "String" {
+        quote! {
+            match value {
+                #field_type::String(val) => val,
+                #mismatch
+            }
+        }
+    } else if ty_str == "bool" {
+        quote! {
+            match value {
+                #field_type::Bool(val) => val,
+                #mismatch
+            }
+        }
+    } else {
+        return Err(syn::Error::new(
+            ty.span(),
+            format!("Type {} is not handled", ty_str),
+        ));
+    };
+    return Ok(conversion);
+}
+
+/// Generates the initializer of the `name` field in `from_fields`, read from
+/// the `column` entry of `fields`.
+fn from_field_initializer(
+    krate: &Path,
+    name: &Ident,
+    column: &str,
+    ty: &Type,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let type_check = field_type_conversion(krate, name, ty)?;
+    return Ok(quote! {
+        #name: match fields.iter().find(|field| field.field_name() == #column) {
+            Some(f) => {
+                let value = f.field_type();
+                #type_check
+            }
+            None => return Err(format!("Field '{}' not found in fields vector", #column)),
+        }
+    });
+}
+
+/// Field level `#[simple_orm(...)]` attributes.
+#[derive(Default)]
+struct FieldAttributes {
+    primary_key: bool,
+    auto_increment: bool,
+    unique: bool,
+    not_null: bool,
+    /// Span of the `skip` key, reported when it is combined with other keys.
+    skip: Option<Span>,
+    column: Option<String>,
+    default: Option<Lit>,
+    foreign_key: Option<(String, String)>,
+}
+
+fn parse_identifier(lit: &LitStr) -> syn::Result<String> {
+    let name = lit.value();
+    validate_identifier(&name, "column", false).map_err(|e| syn::Error::new(lit.span(), e))?;
+    return Ok(name);
+}
+
+fn parse_field_attributes(field: &Field) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident("simple_orm"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("primary_key") {
+                attributes.primary_key = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("auto_increment") {
+                attributes.auto_increment = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("unique") {
+                attributes.unique = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("not_null") {
+                attributes.not_null = true;
+                return Ok(());
+            }
+            if meta.path.is_ident("skip") {
+                attributes.skip = Some(meta.path.span());
+                return Ok(());
+            }
+            if meta.path.is_ident("column") {
+                attributes.column = Some(parse_identifier(&meta.value()?.parse()?)?);
+                return Ok(());
+            }
+            if meta.path.is_ident("default") {
+                let lit: Lit = meta.value()?.parse()?;
+                if !matches!(lit, Lit::Str(_) | Lit::Int(_) | Lit::Float(_) | Lit::Bool(_)) {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "expected a string, integer, float or boolean literal",
+                    ));
                 }
-            };
-            let from_fields_fn = {
-                let field_names = data_values.fields.iter().map(|field| &field.ident);
-                let field_types = data_values.fields.iter().map(|field| &field.ty);
-                let field_type_checks = field_names.clone().zip(field_types).map(|(name, ty)| {
-                    let type_check = if ty.to_token_stream().to_string() == "u8" || ty.to_token_stream().to_string() == "u16" || ty.to_token_stream().to_string() == "u32" || ty.to_token_stream().to_string() == "u64" || ty.to_token_stream().to_string() == "i8" || ty.to_token_stream().to_string() == "i16" || ty.to_token_stream().to_string() == "i32" || ty.to_token_stream().to_string() == "i64" {
-                        quote! {
-                            match f.field_type() {
-                                FieldType::Number(val) => val.try_into().unwrap(),
-                                _ => return Err(format!("Mismatched field type for '{}'",stringify!(id))),
-                            }
-                        }
-                    } else if ty.to_token_stream().to_string() == "&str" || ty.to_token_stream().to_string() == "String" {
-                        quote! {
-                            match f.field_type() {
-                                FieldType::String(val) => val,
-                                _ => return Err(format!("Mismatched field type for '{}'",stringify!(id))),
-                            }
-                        }
-                    } else if ty.to_token_stream().to_string() == "bool" {
-                        quote! {
-                            match f.field_type() {
-                                FieldType::Bool(val) => val,
-                                _ => return Err(format!("Mismatched field type for '{}'",stringify!(id))),
-                            }
-                        }
-                    } else {
-                        panic!("Type {} is not handled", ty.to_token_stream().to_string());
-                    };
-                    quote! {
-                        #name: match fields.iter().find(|field| field.field_name() == stringify!(#name)) {
-                            Some(f) => #type_check,
-                            None => return Err(format!("Field '{}' not found in fields vector", stringify!(#name))),
-                        }
-                    }
-                });
-
-                quote! {
-                    fn from_fields(fields: Vec<DatabaseField>) -> Result<Self, String>
-                    where
-                        Self: Sized,
-                    {
-                        Ok(Self {
-                            #( #field_type_checks ),*
-                        })
-                    }
+                attributes.default = Some(lit);
+                return Ok(());
+            }
+            if meta.path.is_ident("foreign_key") {
+                let content;
+                parenthesized!(content in meta.input);
+                let args = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                if args.len() != 2 {
+                    return Err(meta.error("expected `foreign_key(table, column)`"));
                 }
-            };
-            let output = quote! {
-                use crate::models::{
-                    database_insertable::DatabaseInsertable,
-                    database_field::{DatabaseField, FieldType}
-                };
-                impl DatabaseInsertable for #ident {
-                    fn database_name() -> String
-                    where
-                        Self: Sized,
-                    {
-                        return "aled".to_owned();
-                    }
-                    #fields_value_fn
-                    #from_fields_fn
+                for arg in args.iter() {
+                    validate_identifier(&arg.to_string(), "foreign key", false)
+                        .map_err(|e| syn::Error::new(arg.span(), e))?;
                 }
-            };
-            output.into()
+                attributes.foreign_key = Some((
+                    quote_reserved(&args[0].to_string()),
+                    quote_reserved(&args[1].to_string()),
+                ));
+                return Ok(());
+            }
+            return Err(meta.error(
+                "unsupported simple_orm attribute, expected one of `primary_key`, `auto_increment`, `unique`, `not_null`, `column`, `skip`, `default` or `foreign_key`",
+            ));
+        })?;
+    }
+    let has_other_attributes = attributes.primary_key
+        || attributes.auto_increment
+        || attributes.unique
+        || attributes.not_null
+        || attributes.column.is_some()
+        || attributes.default.is_some()
+        || attributes.foreign_key.is_some();
+    if let Some(skip) = attributes.skip.filter(|_| has_other_attributes) {
+        return Err(syn::Error::new(
+            skip,
+            "`skip` cannot be combined with other simple_orm attributes",
+        ));
+    }
+    if attributes.auto_increment {
+        if !attributes.primary_key {
+            return Err(syn::Error::new(
+                field.span(),
+                "`auto_increment` is only available on a `primary_key` field",
+            ));
+        }
+        if attributes.default.is_some() {
+            return Err(syn::Error::new(
+                field.span(),
+                "`auto_increment` cannot be combined with `default`",
+            ));
+        }
+        let ty_str = field.ty.to_token_stream().to_string();
+        if !["u8", "u16", "u32", "i8", "i16", "i32", "i64"].contains(&ty_str.as_str()) {
+            return Err(syn::Error::new(
+                field.ty.span(),
+                "`auto_increment` is only available on integer fields up to `i64`",
+            ));
+        }
+    }
+    return Ok(attributes);
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let DeriveInput {
+        ident, data, attrs, ..
+    } = input;
+    let StructAttributes { table_name, krate } = parse_struct_attributes(&ident, &attrs)?;
+    let field_type = quote! { #krate::models::database_field::FieldType };
+    let database_field = quote! { #krate::models::database_field::DatabaseField };
+    let data_values = match data {
+        Data::Struct(data_values) => data_values,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Derive trait \"DatabaseInsertable\" is only available for structs",
+            ))
+        }
+    };
+    let mut field_value_initializers = Vec::new();
+    let mut field_type_checks = Vec::new();
+    let mut primary_keys = 0;
+    let mut auto_increment = None;
+    for field in data_values.fields.iter() {
+        let Some(name) = &field.ident else {
+            return Err(syn::Error::new(
+                field.span(),
+                "Derive trait \"DatabaseInsertable\" is only available for structs with named fields",
+            ));
+        };
+        let attributes = parse_field_attributes(field)?;
+        if attributes.skip.is_some() {
+            field_type_checks.push(quote! {
+                #name: Default::default()
+            });
+            continue;
         }
-        Data::Enum(_) => {
-            panic!("Derive trait \"DatabaseInsertable\" is only available for structs")
+        let column = match attributes.column {
+            Some(column) => column,
+            None => {
+                let column = name.to_string();
+                validate_identifier(&column, "column", false)
+                    .map_err(|e| syn::Error::new(name.span(), e))?;
+                column
+            }
+        };
+
+        // Generate code for `fields_value` function
+        let mut modifiers = Vec::new();
+        if attributes.primary_key {
+            primary_keys += 1;
+            modifiers.push(quote! { .is_primary_key() });
+        }
+        if attributes.auto_increment {
+            auto_increment = Some(field.span());
+            modifiers.push(quote! { .is_auto_increment() });
+        }
+        if attributes.unique {
+            modifiers.push(quote! { .is_unique() });
+        }
+        if attributes.not_null || option_inner_type(&field.ty).is_none() {
+            modifiers.push(quote! { .is_mandatory() });
+        }
+        if let Some(default) = &attributes.default {
+            modifiers.push(quote! { .has_default(#field_type::from(#default)) });
         }
-        Data::Union(_) => {
-            panic!("Derive trait \"DatabaseInsertable\" is only available for structs")
+        if let Some((foreign_table, foreign_column)) = &attributes.foreign_key {
+            modifiers.push(quote! { .is_foreign_key(#foreign_table, #foreign_column) });
         }
+        field_value_initializers.push(quote! {
+            #database_field::builder(#column, #field_type::from(self.#name.clone()))#( #modifiers )*.build()
+        });
+
+        // Generate code for `from_fields` function
+        field_type_checks.push(from_field_initializer(&krate, name, &column, &field.ty)?);
+    }
+    if let Some(span) = auto_increment {
+        if primary_keys > 1 {
+            return Err(syn::Error::new(
+                span,
+                "`auto_increment` cannot be part of a composite primary key",
+            ));
+        }
+    }
+
+    return Ok(quote! {
+        impl #krate::models::database_insertable::DatabaseInsertable for #ident {
+            fn database_name() -> String
+            where
+                Self: Sized,
+            {
+                return #table_name.to_owned();
+            }
+
+            fn fields_value(&self) -> Vec<#database_field> {
+                return vec![
+                    #( #field_value_initializers ),*
+                ];
+            }
+
+            fn from_fields(fields: Vec<#database_field>) -> Result<Self, String>
+            where
+                Self: Sized,
+            {
+                Ok(Self {
+                    #( #field_type_checks ),*
+                })
+            }
+        }
+    });
+}
+
+/// Parses the struct level `#[simple_orm(crate = "...")]` attribute of a
+/// `DatabaseResult`, which has no table.
+fn parse_result_attributes(attrs: &[Attribute]) -> syn::Result<Path> {
+    let mut krate: Option<Path> = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("simple_orm")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                let path: LitStr = meta.value()?.parse()?;
+                krate = Some(path.parse()?);
+                return Ok(());
+            }
+            return Err(meta.error("unsupported simple_orm attribute, expected `crate`"));
+        })?;
+    }
+    return Ok(krate.unwrap_or_else(|| syn::parse_quote!(::simple_orm)));
+}
+
+fn expand_result(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let DeriveInput {
+        ident, data, attrs, ..
+    } = input;
+    let krate = parse_result_attributes(&attrs)?;
+    let field_type = quote! { #krate::models::database_field::FieldType };
+    let database_field = quote! { #krate::models::database_field::DatabaseField };
+    let data_values = match data {
+        Data::Struct(data_values) => data_values,
+        _ => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Derive trait \"DatabaseResult\" is only available for structs",
+            ))
+        }
+    };
+    let mut result_fields = Vec::new();
+    let mut field_type_checks = Vec::new();
+    for field in data_values.fields.iter() {
+        let Some(name) = &field.ident else {
+            return Err(syn::Error::new(
+                field.span(),
+                "Derive trait \"DatabaseResult\" is only available for structs with named fields",
+            ));
+        };
+        let attributes = parse_field_attributes(field)?;
+        if attributes.primary_key
+            || attributes.unique
+            || attributes.not_null
+            || attributes.default.is_some()
+            || attributes.foreign_key.is_some()
+        {
+            return Err(syn::Error::new(
+                field.span(),
+                "only `column` and `skip` are supported on DatabaseResult fields",
+            ));
+        }
+        if attributes.skip.is_some() {
+            field_type_checks.push(quote! {
+                #name: Default::default()
+            });
+            continue;
+        }
+        let column = match attributes.column {
+            Some(column) => column,
+            None => {
+                let column = name.to_string();
+                validate_identifier(&column, "column", false)
+                    .map_err(|e| syn::Error::new(name.span(), e))?;
+                column
+            }
+        };
+        let ty = &field.ty;
+        result_fields.push(quote! {
+            #database_field::new(#column, #field_type::from(<#ty as Default>::default()))
+        });
+        field_type_checks.push(from_field_initializer(&krate, name, &column, ty)?);
+    }
+
+    return Ok(quote! {
+        impl #krate::models::database_result::DatabaseResult for #ident {
+            fn result_fields() -> Vec<#database_field>
+            where
+                Self: Sized,
+            {
+                return vec![
+                    #( #result_fields ),*
+                ];
+            }
+
+            fn from_fields(fields: Vec<#database_field>) -> Result<Self, String>
+            where
+                Self: Sized,
+            {
+                Ok(Self {
+                    #( #field_type_checks ),*
+                })
+            }
+        }
+    });
+}
+
+#[proc_macro_derive(DatabaseInsertable, attributes(simple_orm))]
+pub fn derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    return match expand(input) {
+        Ok(output) => output.into(),
+        Err(e) => e.to_compile_error().into(),
+    };
+}
+
+#[proc_macro_derive(DatabaseResult, attributes(simple_orm))]
+pub fn derive_result(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    return match expand_result(input) {
+        Ok(output) => output.into(),
+        Err(e) => e.to_compile_error().into(),
     };
 }
@@ -1 +1,5 @@
 pub mod postgres;
+pub mod postgres_migrations;
+#[cfg(feature = "tls")]
+pub mod postgres_tls;
+pub mod sqlite;
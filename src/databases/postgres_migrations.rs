@@ -0,0 +1,328 @@
+use std::{future::Future, pin::Pin};
+
+use sha2::{Digest, Sha256};
+use tokio_postgres::{types::ToSql, Client};
+
+use super::postgres::{PostgresDB, PostgresTransaction};
+use crate::models::{database_error::DatabaseError, database_transaction::DatabaseTransaction};
+
+/// Table keeping track of the applied migrations.
+const MIGRATIONS_TABLE: &str = "_simple_orm_migrations";
+
+/// Key of the advisory lock held by the transaction of each migration, "simple_o" in ASCII.
+const MIGRATIONS_LOCK: i64 = 0x7369_6d70_6c65_5f6f;
+
+/// Future returned by the closure of a Rust migration step.
+pub type MigrationFuture<'a> = Pin<Box<dyn Future<Output = Result<(), DatabaseError>> + Send + 'a>>;
+
+type MigrationFn =
+    Box<dyn for<'a, 'b> Fn(&'a PostgresTransaction<'b>) -> MigrationFuture<'a> + Send + Sync>;
+
+enum MigrationStep {
+    Sql(String),
+    Rust(MigrationFn),
+}
+
+impl MigrationStep {
+    async fn run(&self, transaction: &PostgresTransaction<'_>) -> Result<(), DatabaseError> {
+        return match self {
+            Self::Sql(sql) => transaction.batch_execute(sql).await,
+            Self::Rust(run) => run(transaction).await,
+        };
+    }
+}
+
+/// Versioned change of the schema, applied in a transaction of its own.
+pub struct Migration {
+    version: i64,
+    name: String,
+    up: MigrationStep,
+    down: Option<MigrationStep>,
+}
+
+impl Migration {
+    /// Migration running the `up` SQL statements.
+    pub fn sql(version: i64, name: &str, up: &str) -> Self {
+        return Self {
+            version,
+            name: name.to_owned(),
+            up: MigrationStep::Sql(up.to_owned()),
+            down: None,
+        };
+    }
+
+    /// Migration running the `up` closure, given the transaction of the
+    /// migration.
+    pub fn rust<F>(version: i64, name: &str, up: F) -> Self
+    where
+        F: for<'a, 'b> Fn(&'a PostgresTransaction<'b>) -> MigrationFuture<'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        return Self {
+            version,
+            name: name.to_owned(),
+            up: MigrationStep::Rust(Box::new(up)),
+            down: None,
+        };
+    }
+
+    /// Reverts the migration with the `down` SQL statements.
+    pub fn down_sql(mut self, down: &str) -> Self {
+        self.down = Some(MigrationStep::Sql(down.to_owned()));
+        return self;
+    }
+
+    /// Reverts the migration with the `down` closure.
+    pub fn down_rust<F>(mut self, down: F) -> Self
+    where
+        F: for<'a, 'b> Fn(&'a PostgresTransaction<'b>) -> MigrationFuture<'a>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.down = Some(MigrationStep::Rust(Box::new(down)));
+        return self;
+    }
+
+    pub fn version(&self) -> i64 {
+        return self.version;
+    }
+
+    pub fn name(&self) -> String {
+        return self.name.clone();
+    }
+
+    /// SHA-256 of the up SQL statements, in hexadecimal. The code of a Rust
+    /// migration cannot be read, so only its name is checked. The kind of the
+    /// step is hashed too, a Rust migration never matching an SQL one.
+    pub fn checksum(&self) -> String {
+        let content = match &self.up {
+            MigrationStep::Sql(sql) => format!("sql:{}", sql),
+            MigrationStep::Rust(_) => format!("rust:{}", self.name),
+        };
+        return Sha256::digest(content.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+    }
+}
+
+/// Ordered set of migrations, applied by
+/// [`PostgresDB::migrate_up`](super::postgres::PostgresDB::migrate_up) and
+/// reverted by
+/// [`PostgresDB::migrate_down`](super::postgres::PostgresDB::migrate_down).
+#[derive(Default)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+}
+
+impl Migrator {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn migration(mut self, migration: Migration) -> Self {
+        self.migrations.push(migration);
+        self.migrations.sort_by_key(|m| m.version);
+        return self;
+    }
+
+    pub fn migrations(&self) -> &[Migration] {
+        return &self.migrations;
+    }
+
+    /// Applies the migrations up to `target`, every one when `None`, and
+    /// returns the versions applied.
+    pub(super) async fn up(
+        &self,
+        client: &mut Client,
+        target: Option<i64>,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let req = format!(
+            "INSERT INTO {}(version, name, checksum) VALUES($1, $2, $3);",
+            MIGRATIONS_TABLE
+        );
+        let mut versions = Vec::new();
+        loop {
+            let transaction = Self::begin_locked(client).await?;
+            let applied = self.applied(&transaction).await?;
+            let pending = self.migrations.iter().find(|m| {
+                !applied.contains(&m.version) && target.is_none_or(|target| m.version <= target)
+            });
+            let Some(migration) = pending else {
+                transaction.commit().await?;
+                return Ok(versions);
+            };
+            migration.up.run(&transaction).await?;
+            let checksum = migration.checksum();
+            let params: [&(dyn ToSql + Sync); 3] = [&migration.version, &migration.name, &checksum];
+            if let Err(e) = transaction.transaction.execute(&req, &params).await {
+                return Err(PostgresDB::map_error(e, &req));
+            }
+            transaction.commit().await?;
+            versions.push(migration.version);
+        }
+    }
+
+    /// Reverts the migrations above `target`, latest first, and returns the
+    /// versions reverted.
+    pub(super) async fn down(
+        &self,
+        client: &mut Client,
+        target: i64,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let req = format!("DELETE FROM {} WHERE version = $1;", MIGRATIONS_TABLE);
+        let mut versions = Vec::new();
+        loop {
+            let transaction = Self::begin_locked(client).await?;
+            let applied = self.applied(&transaction).await?;
+            // Every migration is checked to be reversible before reverting any.
+            let mut reverted = Vec::new();
+            for version in applied.iter().rev().filter(|v| **v > target) {
+                let migration = self.find(*version)?;
+                match &migration.down {
+                    Some(down) => reverted.push((migration.version, down)),
+                    None => {
+                        return Err(DatabaseError::IrreversibleMigration {
+                            version: migration.version,
+                        })
+                    }
+                }
+            }
+            let Some((version, down)) = reverted.into_iter().next() else {
+                transaction.commit().await?;
+                return Ok(versions);
+            };
+            down.run(&transaction).await?;
+            if let Err(e) = transaction.transaction.execute(&req, &[&version]).await {
+                return Err(PostgresDB::map_error(e, &req));
+            }
+            transaction.commit().await?;
+            versions.push(version);
+        }
+    }
+
+    /// Opens the transaction of a migration once it holds the lock serializing
+    /// concurrent instances. The lock is released with the transaction, even
+    /// when it is dropped half way.
+    async fn begin_locked(client: &mut Client) -> Result<PostgresTransaction<'_>, DatabaseError> {
+        let transaction = PostgresDB::begin_on(client, None).await?;
+        let req = "SELECT pg_advisory_xact_lock($1);";
+        if let Err(e) = transaction
+            .transaction
+            .execute(req, &[&MIGRATIONS_LOCK])
+            .await
+        {
+            return Err(PostgresDB::map_error(e, req));
+        }
+        return Ok(transaction);
+    }
+
+    fn find(&self, version: i64) -> Result<&Migration, DatabaseError> {
+        return match self.migrations.iter().find(|m| m.version == version) {
+            Some(migration) => Ok(migration),
+            None => Err(DatabaseError::MissingMigration { version }),
+        };
+    }
+
+    /// Versions of the applied migrations in order, after checking they are
+    /// all known and unmodified.
+    async fn applied(
+        &self,
+        transaction: &PostgresTransaction<'_>,
+    ) -> Result<Vec<i64>, DatabaseError> {
+        let client = &transaction.transaction;
+        for pair in self.migrations.windows(2) {
+            if pair[0].version == pair[1].version {
+                return Err(DatabaseError::DuplicateMigration {
+                    version: pair[0].version,
+                });
+            }
+        }
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+version BIGINT PRIMARY KEY,
+name TEXT NOT NULL,
+checksum TEXT NOT NULL,
+applied_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);",
+            MIGRATIONS_TABLE
+        );
+        if let Err(e) = client.batch_execute(&create).await {
+            return Err(PostgresDB::map_error(e, &create));
+        }
+        let req = format!(
+            "SELECT version, checksum FROM {} ORDER BY version;",
+            MIGRATIONS_TABLE
+        );
+        let rows = match client.query(&req, &[]).await {
+            Ok(rows) => rows,
+            Err(e) => return Err(PostgresDB::map_error(e, &req)),
+        };
+        let mut versions = Vec::new();
+        for row in rows {
+            let (version, checksum) = match (row.try_get(0), row.try_get::<_, String>(1)) {
+                (Ok(version), Ok(checksum)) => (version, checksum),
+                (Err(e), _) | (_, Err(e)) => {
+                    return Err(DatabaseError::Decode {
+                        field: None,
+                        source: Box::new(e),
+                    })
+                }
+            };
+            if self.find(version)?.checksum() != checksum {
+                return Err(DatabaseError::MigrationChecksumMismatch { version });
+            }
+            versions.push(version);
+        }
+        return Ok(versions);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::databases::postgres_migrations::{Migration, Migrator};
+
+    #[test]
+    pub fn ordered_by_version() {
+        let migrator = Migrator::new()
+            .migration(Migration::sql(3, "third", "SELECT 3;"))
+            .migration(Migration::sql(1, "first", "SELECT 1;"))
+            .migration(Migration::sql(2, "second", "SELECT 2;"));
+        let versions = migrator
+            .migrations()
+            .iter()
+            .map(|m| m.version())
+            .collect::<Vec<i64>>();
+        assert_eq!(versions, vec![1, 2, 3]);
+    }
+
+    #[test]
+    pub fn checksum() {
+        let migration = Migration::sql(1, "create", "CREATE TABLE items (id BIGINT);");
+        assert_eq!(migration.checksum().len(), 64);
+        assert_eq!(
+            migration.checksum(),
+            Migration::sql(1, "renamed", "CREATE TABLE items (id BIGINT);")
+                .down_sql("DROP TABLE items;")
+                .checksum()
+        );
+        assert_ne!(
+            migration.checksum(),
+            Migration::sql(1, "create", "CREATE TABLE items (id INTEGER);").checksum()
+        );
+        let rust = Migration::rust(2, "seed", |transaction| {
+            Box::pin(async move { transaction.batch_execute("SELECT 1;").await })
+        });
+        assert_eq!(
+            rust.checksum(),
+            Migration::rust(2, "seed", |_| Box::pin(async { Ok(()) })).checksum()
+        );
+        assert_ne!(
+            rust.checksum(),
+            Migration::sql(2, "seed", "seed").checksum()
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use std::sync::Arc;
+
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    client::WebPkiServerVerifier,
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_postgres::Config;
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::models::database_error::DatabaseError;
+
+/// `sslmode` of a connection string, following the libpq semantics.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SslMode {
+    /// Never use TLS.
+    Disable,
+    /// Use TLS if the server supports it, without verifying its certificate. This
+    /// is the default.
+    Prefer,
+    /// Always use TLS, without verifying the server certificate.
+    Require,
+    /// Always use TLS and check the server certificate is signed by a trusted
+    /// authority.
+    VerifyCa,
+    /// Same as [`Self::VerifyCa`], also checking the certificate was issued for
+    /// the host connected to.
+    VerifyFull,
+}
+
+impl SslMode {
+    fn parse(value: &str) -> Option<Self> {
+        return match value {
+            "disable" => Some(Self::Disable),
+            "prefer" => Some(Self::Prefer),
+            "require" => Some(Self::Require),
+            "verify-ca" => Some(Self::VerifyCa),
+            "verify-full" => Some(Self::VerifyFull),
+            _ => None,
+        };
+    }
+}
+
+/// TLS settings of Postgres connections, the mode being read from the `sslmode`
+/// of the connection string.
+#[derive(Debug)]
+pub struct PostgresTls {
+    roots: RootCertStore,
+    custom_roots: bool,
+    client_auth: Option<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)>,
+}
+
+impl Default for PostgresTls {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Clone for PostgresTls {
+    fn clone(&self) -> Self {
+        return Self {
+            roots: self.roots.clone(),
+            custom_roots: self.custom_roots,
+            client_auth: self
+                .client_auth
+                .as_ref()
+                .map(|(chain, key)| (chain.clone(), key.clone_key())),
+        };
+    }
+}
+
+impl PostgresTls {
+    /// Trusts the Mozilla root certificates.
+    pub fn new() -> Self {
+        return Self {
+            roots: RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+            custom_roots: false,
+            client_auth: None,
+        };
+    }
+
+    /// Trusts the certificates of `pem` instead of the Mozilla root certificates,
+    /// call it again to trust several files.
+    pub fn root_certificates_pem(mut self, pem: &[u8]) -> Result<Self, DatabaseError> {
+        if !self.custom_roots {
+            self.roots = RootCertStore::empty();
+            self.custom_roots = true;
+        }
+        for cert in Self::read_certificates(pem)? {
+            if let Err(e) = self.roots.add(cert) {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                });
+            }
+        }
+        return Ok(self);
+    }
+
+    /// Authenticates with the certificate chain of `cert_pem` and its private key.
+    pub fn client_certificate_pem(
+        mut self,
+        cert_pem: &[u8],
+        key_pem: &[u8],
+    ) -> Result<Self, DatabaseError> {
+        let chain = Self::read_certificates(cert_pem)?;
+        let key = match rustls_pemfile::private_key(&mut &key_pem[..]) {
+            Ok(Some(key)) => key,
+            Ok(None) => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: "no private key found in PEM".into(),
+                })
+            }
+            Err(e) => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                })
+            }
+        };
+        self.client_auth = Some((chain, key));
+        return Ok(self);
+    }
+
+    fn read_certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, DatabaseError> {
+        let certs = match rustls_pemfile::certs(&mut &pem[..]).collect::<Result<Vec<_>, _>>() {
+            Ok(certs) => certs,
+            Err(e) => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                })
+            }
+        };
+        if certs.is_empty() {
+            return Err(DatabaseError::ConnectionFailed {
+                source: "no certificate found in PEM".into(),
+            });
+        }
+        return Ok(certs);
+    }
+
+    /// Parses `params` and builds the connector matching its `sslmode`.
+    pub(super) fn configure(
+        &self,
+        params: &str,
+    ) -> Result<(Config, MakeRustlsConnect), DatabaseError> {
+        let (params, ssl_mode) = split_ssl_mode(params)?;
+        let mut config = match params.parse::<Config>() {
+            Ok(config) => config,
+            Err(e) => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                })
+            }
+        };
+        config.ssl_mode(match ssl_mode {
+            SslMode::Disable => tokio_postgres::config::SslMode::Disable,
+            SslMode::Prefer => tokio_postgres::config::SslMode::Prefer,
+            _ => tokio_postgres::config::SslMode::Require,
+        });
+        let client_config = match self.client_config(ssl_mode) {
+            Ok(client_config) => client_config,
+            Err(e) => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                })
+            }
+        };
+        return Ok((config, MakeRustlsConnect::new(client_config)));
+    }
+
+    fn client_config(&self, ssl_mode: SslMode) -> Result<ClientConfig, rustls::Error> {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?;
+        let roots = Arc::new(self.roots.clone());
+        let builder = match ssl_mode {
+            SslMode::VerifyFull => builder.with_root_certificates(roots),
+            SslMode::VerifyCa => {
+                let verifier =
+                    match WebPkiServerVerifier::builder_with_provider(roots, provider).build() {
+                        Ok(verifier) => verifier,
+                        Err(e) => return Err(rustls::Error::General(e.to_string())),
+                    };
+                builder
+                    .dangerous()
+                    .with_custom_certificate_verifier(Arc::new(AnyNameVerifier { verifier }))
+            }
+            SslMode::Disable | SslMode::Prefer | SslMode::Require => builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(NoCertificateVerifier { provider })),
+        };
+        return match &self.client_auth {
+            Some((chain, key)) => builder.with_client_auth_cert(chain.clone(), key.clone_key()),
+            None => Ok(builder.with_no_client_auth()),
+        };
+    }
+}
+
+/// Removes `sslmode` from `params`, a `key=value` or URL connection string, as
+/// tokio-postgres does not understand the `verify-*` modes.
+fn split_ssl_mode(params: &str) -> Result<(String, SslMode), DatabaseError> {
+    let mut ssl_mode = SslMode::Prefer;
+    let mut base = String::new();
+    let is_url = params.starts_with("postgres://") || params.starts_with("postgresql://");
+    let pairs = match is_url {
+        true => match params.split_once('?') {
+            Some((url, query)) => {
+                base = url.to_owned();
+                query.split('&').map(|p| p.to_owned()).collect()
+            }
+            None => return Ok((params.to_owned(), ssl_mode)),
+        },
+        false => split_key_values(params),
+    };
+    let mut options = Vec::new();
+    for pair in pairs {
+        let (key, value) = match pair.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (pair.trim(), ""),
+        };
+        if key != "sslmode" {
+            options.push(pair);
+            continue;
+        }
+        let value = value.trim_matches('\'');
+        ssl_mode = match SslMode::parse(value) {
+            Some(mode) => mode,
+            None => {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: format!("invalid sslmode '{}'", value).into(),
+                })
+            }
+        };
+    }
+    let params = match is_url {
+        true if options.is_empty() => base,
+        true => format!("{}?{}", base, options.join("&")),
+        false => options.join(" "),
+    };
+    return Ok((params, ssl_mode));
+}
+
+/// Splits a `key=value` connection string in its pairs, keeping quoted values
+/// whole.
+fn split_key_values(params: &str) -> Vec<String> {
+    let mut pairs = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut chars = params.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '\'' {
+            quoted = !quoted;
+        } else if c.is_whitespace() && !quoted {
+            while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                chars.next();
+            }
+            // Spaces are allowed around the `=` sign.
+            if current.ends_with('=') || chars.peek() == Some(&'=') {
+                continue;
+            }
+            pairs.push(std::mem::take(&mut current));
+            continue;
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        pairs.push(current);
+    }
+    return pairs;
+}
+
+/// Verifier of the `require` mode, which only checks the server owns the key of
+/// the certificate it sent.
+#[derive(Debug)]
+struct NoCertificateVerifier {
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for NoCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        return Ok(ServerCertVerified::assertion());
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        return verify_tls12_signature(message, cert, dss, algorithms);
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        let algorithms = &self.provider.signature_verification_algorithms;
+        return verify_tls13_signature(message, cert, dss, algorithms);
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        return self
+            .provider
+            .signature_verification_algorithms
+            .supported_schemes();
+    }
+}
+
+/// Verifier of the `verify-ca` mode, which accepts certificates issued for any
+/// host.
+#[derive(Debug)]
+struct AnyNameVerifier {
+    verifier: Arc<WebPkiServerVerifier>,
+}
+
+impl ServerCertVerifier for AnyNameVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let res = self.verifier.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        );
+        return match res {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            res => res,
+        };
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        return self.verifier.verify_tls12_signature(message, cert, dss);
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        return self.verifier.verify_tls13_signature(message, cert, dss);
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        return self.verifier.supported_verify_schemes();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::databases::postgres_tls::{split_key_values, split_ssl_mode, SslMode};
+
+    #[test]
+    pub fn key_values() {
+        assert_eq!(
+            split_key_values("host=localhost  password = 'a b\\'c' user=postgres"),
+            vec!["host=localhost", "password='a b\\'c'", "user=postgres"]
+        );
+    }
+
+    #[test]
+    pub fn ssl_mode_from_key_values() {
+        let (params, mode) =
+            split_ssl_mode("host=localhost sslmode=verify-full user=postgres").unwrap();
+        assert_eq!(params, "host=localhost user=postgres");
+        assert_eq!(mode, SslMode::VerifyFull);
+        let (params, mode) = split_ssl_mode("host=localhost sslmode = 'verify-ca'").unwrap();
+        assert_eq!(params, "host=localhost");
+        assert_eq!(mode, SslMode::VerifyCa);
+        let (params, mode) = split_ssl_mode("host=localhost").unwrap();
+        assert_eq!(params, "host=localhost");
+        assert_eq!(mode, SslMode::Prefer);
+    }
+
+    #[test]
+    pub fn ssl_mode_from_url() {
+        let (params, mode) =
+            split_ssl_mode("postgres://postgres@localhost/db?sslmode=require&connect_timeout=5")
+                .unwrap();
+        assert_eq!(params, "postgres://postgres@localhost/db?connect_timeout=5");
+        assert_eq!(mode, SslMode::Require);
+        let (params, mode) = split_ssl_mode("postgresql://localhost?sslmode=disable").unwrap();
+        assert_eq!(params, "postgresql://localhost");
+        assert_eq!(mode, SslMode::Disable);
+    }
+
+    #[test]
+    pub fn invalid_ssl_mode() {
+        assert!(split_ssl_mode("host=localhost sslmode=always").is_err());
+    }
+}
@@ -0,0 +1,3096 @@
+use std::{
+    mem, panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use async_trait::async_trait;
+use rusqlite::{
+    ffi, params_from_iter,
+    types::{ToSqlOutput, Value},
+    Connection, ErrorCode, Row, ToSql, TransactionBehavior,
+};
+use tokio::task;
+
+use crate::models::{
+    database_aggregate::{AggregateExpression, AggregateFunction},
+    database_condition::{
+        ConditionExpression, ConditionOperator, ConditionValue, DatabaseCondition,
+    },
+    database_error::DatabaseError,
+    database_field::{DatabaseField, FieldType},
+    database_insertable::DatabaseInsertable,
+    database_query::{GroupedQuery, NullsOrder, QueryOptions, SortDirection},
+    database_result::DatabaseResult,
+    database_schema::{
+        ColumnSchema, ConstraintKind, SchemaChange, SchemaPlan, TableConstraint, TableSchema,
+    },
+    database_transaction::{DatabaseTransaction, DatabaseTransactional, IsolationLevel},
+    database_type::DatabaseType,
+    database_upsert::{UpsertOptions, UpsertOutcome},
+};
+
+/// Bind parameters a statement can have at most, `SQLITE_MAX_VARIABLE_NUMBER`
+/// of the bundled SQLite.
+const MAX_PARAMETERS: usize = 32766;
+
+impl ToSql for FieldType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match self {
+            FieldType::SmallInt(val) => val.to_sql(),
+            FieldType::Int(val) => val.to_sql(),
+            FieldType::BigInt(val) => val.to_sql(),
+            // Fails above i64::MAX, SQLite integers being signed 64 bits.
+            FieldType::UnsignedBigInt(val) => val.to_sql(),
+            FieldType::HugeInt(val) => match i64::try_from(*val) {
+                Ok(val) => Ok(ToSqlOutput::from(val)),
+                Err(e) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(e))),
+            },
+            FieldType::Real(val) => val.to_sql(),
+            FieldType::Double(val) => val.to_sql(),
+            FieldType::String(val) => Ok(ToSqlOutput::from(val.as_str())),
+            FieldType::Bool(val) => Ok(ToSqlOutput::Owned(Value::Integer(*val as i64))),
+            FieldType::Null(_) => Ok(ToSqlOutput::Owned(Value::Null)),
+        }
+    }
+}
+
+/// SQLite backend, either on a file or fully in memory with `":memory:"`.
+///
+/// rusqlite is synchronous, so statements run one at a time in the blocking
+/// thread pool of tokio rather than on the runtime. This is fine for tests and
+/// small tools, prefer [`PostgresPool`](super::postgres::PostgresPool) for
+/// anything concurrent.
+pub struct SqliteDB {
+    // rusqlite connections cannot be shared between threads on their own, and
+    // the blocking tasks using it may outlive the call which spawned them.
+    connection: Arc<Mutex<Connection>>,
+    /// Set when a [`SqliteTransaction`] is dropped unfinished, its rollback
+    /// running before the next statement so that dropping it never blocks.
+    rollback_pending: Arc<AtomicBool>,
+}
+
+impl SqliteDB {
+    pub async fn new(path: &str) -> Result<Self, DatabaseError> {
+        let path = path.to_owned();
+        let opened = Self::blocking(move || {
+            let connection = match Connection::open(path) {
+                Ok(c) => c,
+                Err(e) => {
+                    return Err(DatabaseError::ConnectionFailed {
+                        source: Box::new(e),
+                    })
+                }
+            };
+            // Match Postgres, where LIKE is case sensitive and ILIKE is not.
+            if let Err(e) = connection.execute_batch("PRAGMA case_sensitive_like = ON;") {
+                return Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                });
+            }
+            return Ok(connection);
+        });
+        return Ok(Self {
+            connection: Arc::new(Mutex::new(opened.await?)),
+            rollback_pending: Arc::new(AtomicBool::new(false)),
+        });
+    }
+
+    fn lock(connection: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+        // Statements are atomic, a panic while holding the lock cannot leave the
+        // connection in an inconsistent state.
+        return connection.lock().unwrap_or_else(|e| e.into_inner());
+    }
+
+    /// Runs `f` in the blocking thread pool, a panic in it being resumed in
+    /// the calling task.
+    async fn blocking<T: Send + 'static>(
+        f: impl FnOnce() -> Result<T, DatabaseError> + Send + 'static,
+    ) -> Result<T, DatabaseError> {
+        return match task::spawn_blocking(f).await {
+            Ok(res) => res,
+            Err(e) => match e.try_into_panic() {
+                Ok(panic) => panic::resume_unwind(panic),
+                // Only happens when the runtime shuts down.
+                Err(e) => Err(DatabaseError::ConnectionFailed {
+                    source: Box::new(e),
+                }),
+            },
+        };
+    }
+
+    /// Runs `f` on the connection, once the statements run before are done.
+    async fn run<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> Result<T, DatabaseError> + Send + 'static,
+    ) -> Result<T, DatabaseError> {
+        let connection = Arc::clone(&self.connection);
+        let rollback_pending = Arc::clone(&self.rollback_pending);
+        return Self::blocking(move || {
+            let mut connection = Self::lock(&connection);
+            // SQLite may already have rolled the transaction back on an error.
+            if rollback_pending.swap(false, Ordering::SeqCst) && !connection.is_autocommit() {
+                if let Err(e) = connection.execute_batch("ROLLBACK;") {
+                    return Err(Self::map_error(e, "ROLLBACK"));
+                }
+            }
+            return f(&mut connection);
+        })
+        .await;
+    }
+
+    /// Runs `f` all or nothing: in a transaction started with `behavior`, or
+    /// in a savepoint within the one opened by [`DatabaseTransactional::begin`].
+    fn atomic<T>(
+        connection: &mut Connection,
+        behavior: TransactionBehavior,
+        f: impl FnOnce(&Connection) -> Result<T, DatabaseError>,
+    ) -> Result<T, DatabaseError> {
+        if !connection.is_autocommit() {
+            let savepoint = match connection.savepoint() {
+                Ok(s) => s,
+                Err(e) => return Err(Self::map_error(e, "SAVEPOINT")),
+            };
+            let res = f(&savepoint)?;
+            if let Err(e) = savepoint.commit() {
+                return Err(Self::map_error(e, "RELEASE"));
+            }
+            return Ok(res);
+        }
+        let transaction = match connection.transaction_with_behavior(behavior) {
+            Ok(t) => t,
+            Err(e) => return Err(Self::map_error(e, "BEGIN")),
+        };
+        let res = f(&transaction)?;
+        if let Err(e) = transaction.commit() {
+            return Err(Self::map_error(e, "COMMIT"));
+        }
+        return Ok(res);
+    }
+
+    /// Runs the `sql` statements, separated by semicolons, which cannot have
+    /// parameters.
+    async fn batch_execute(&self, sql: &str) -> Result<(), DatabaseError> {
+        let sql = sql.to_owned();
+        return self
+            .run(move |connection| match connection.execute_batch(&sql) {
+                Ok(()) => Ok(()),
+                Err(e) => Err(Self::map_error(e, &sql)),
+            })
+            .await;
+    }
+
+    fn get_string_operator(operator: ConditionOperator) -> &'static str {
+        match operator {
+            ConditionOperator::Eq => "=",
+            ConditionOperator::Ne => "<>",
+            ConditionOperator::Gt => ">",
+            ConditionOperator::Gte => ">=",
+            ConditionOperator::Lt => "<",
+            ConditionOperator::Lte => "<=",
+            ConditionOperator::In => "IN",
+            ConditionOperator::NotIn => "NOT IN",
+            ConditionOperator::Like => "LIKE",
+            ConditionOperator::ILike => "LIKE",
+            ConditionOperator::Between => "BETWEEN",
+            ConditionOperator::IsNull => "IS NULL",
+            ConditionOperator::IsNotNull => "IS NOT NULL",
+        }
+    }
+
+    fn get_string_aggregate(function: AggregateFunction) -> &'static str {
+        match function {
+            AggregateFunction::Count => "COUNT",
+            AggregateFunction::Sum => "SUM",
+            AggregateFunction::Avg => "AVG",
+            AggregateFunction::Min => "MIN",
+            AggregateFunction::Max => "MAX",
+        }
+    }
+
+    fn get_string_type(field_type: FieldType) -> &'static str {
+        match field_type {
+            FieldType::SmallInt(_) => "SMALLINT",
+            FieldType::Int(_) => "INTEGER",
+            FieldType::BigInt(_) => "BIGINT",
+            FieldType::UnsignedBigInt(_) => "UNSIGNED BIGINT",
+            FieldType::HugeInt(_) => "NUMERIC",
+            FieldType::Real(_) => "REAL",
+            FieldType::Double(_) => "DOUBLE",
+            FieldType::String(_) => "TEXT",
+            FieldType::Bool(_) => "BOOLEAN",
+            FieldType::Null(field_type) => Self::get_string_type(*field_type),
+        }
+    }
+
+    /// Literal used in DDL, where values cannot be bound.
+    fn stringify_literal(value: FieldType) -> String {
+        return match value {
+            FieldType::String(val) => format!("'{}'", val.replace('\'', "''")),
+            FieldType::Bool(val) => val.to_string().to_uppercase(),
+            FieldType::Null(_) => "NULL".to_owned(),
+            FieldType::Real(val) => val.to_string(),
+            FieldType::Double(val) => val.to_string(),
+            other => other.integer_value().unwrap_or_default().to_string(),
+        };
+    }
+
+    fn stringify_condition(cond: &DatabaseCondition, params: &mut Vec<FieldType>) -> String {
+        // SQLite has no ILIKE, LIKE is made case sensitive on connection so both
+        // sides are lowered instead.
+        let name = match cond.operator() {
+            ConditionOperator::ILike => format!("LOWER({})", cond.name()),
+            _ => cond.name(),
+        };
+        let operator = Self::get_string_operator(cond.operator());
+        return match cond.value() {
+            ConditionValue::Empty => format!("{} {}", name, operator),
+            ConditionValue::Single(value) => {
+                params.push(value);
+                match cond.operator() {
+                    ConditionOperator::ILike => {
+                        format!("{} {} LOWER(?{})", name, operator, params.len())
+                    }
+                    _ => format!("{} {} ?{}", name, operator, params.len()),
+                }
+            }
+            ConditionValue::Range(low, high) => {
+                params.push(low);
+                params.push(high);
+                format!(
+                    "{} {} ?{} AND ?{}",
+                    name,
+                    operator,
+                    params.len() - 1,
+                    params.len()
+                )
+            }
+            // `IN ()` is not valid SQL, an empty list matches nothing (or everything
+            // for `NOT IN`).
+            ConditionValue::Multiple(values) if values.is_empty() => match cond.operator() {
+                ConditionOperator::NotIn => "TRUE".to_owned(),
+                _ => "FALSE".to_owned(),
+            },
+            ConditionValue::Multiple(values) => {
+                let placeholders = values
+                    .into_iter()
+                    .map(|value| {
+                        params.push(value);
+                        format!("?{}", params.len())
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("{} {} ({})", name, operator, placeholders)
+            }
+        };
+    }
+
+    fn stringify_group(
+        conditions: &[ConditionExpression],
+        separator: &str,
+        empty: &str,
+        params: &mut Vec<FieldType>,
+    ) -> String {
+        return match conditions {
+            [] => empty.to_owned(),
+            [condition] => Self::stringify_expression(condition, params),
+            _ => format!(
+                "({})",
+                conditions
+                    .iter()
+                    .map(|c| Self::stringify_expression(c, params))
+                    .collect::<Vec<String>>()
+                    .join(separator)
+            ),
+        };
+    }
+
+    fn stringify_expression(
+        expression: &ConditionExpression,
+        params: &mut Vec<FieldType>,
+    ) -> String {
+        return match expression {
+            ConditionExpression::Condition(cond) => Self::stringify_condition(cond, params),
+            ConditionExpression::And(conds) => {
+                Self::stringify_group(conds, " AND ", "TRUE", params)
+            }
+            ConditionExpression::Or(conds) => Self::stringify_group(conds, " OR ", "FALSE", params),
+            ConditionExpression::Not(cond) => {
+                format!("NOT ({})", Self::stringify_expression(cond, params))
+            }
+        };
+    }
+
+    /// Builds the ` WHERE ...` clause, pushing every condition value in `params`
+    /// so that they are bound as `?n` rather than spliced into the request.
+    fn stringify_conditions(
+        conditions: &ConditionExpression,
+        params: &mut Vec<FieldType>,
+    ) -> String {
+        if conditions.is_empty() {
+            return String::new();
+        }
+        return format!(" WHERE {}", Self::stringify_expression(conditions, params));
+    }
+
+    /// [`Self::stringify_conditions`] of a request on `D`, every condition
+    /// having to apply to one of its fields.
+    fn model_conditions<D: DatabaseInsertable>(
+        conditions: &ConditionExpression,
+        params: &mut Vec<FieldType>,
+    ) -> Result<String, DatabaseError> {
+        conditions.validate(&D::default().fields_value())?;
+        return Ok(Self::stringify_conditions(conditions, params));
+    }
+
+    fn stringify_options(options: &QueryOptions) -> String {
+        let mut req = String::new();
+        let orders = options
+            .sort_orders()
+            .iter()
+            .map(|order| {
+                let direction = match order.direction() {
+                    SortDirection::Asc => "ASC",
+                    SortDirection::Desc => "DESC",
+                };
+                let nulls = match order.nulls() {
+                    Some(NullsOrder::First) => " NULLS FIRST",
+                    Some(NullsOrder::Last) => " NULLS LAST",
+                    None => "",
+                };
+                format!("{} {}{}", order.column(), direction, nulls)
+            })
+            .collect::<Vec<String>>();
+        if !orders.is_empty() {
+            req.push_str(&format!(" ORDER BY {}", orders.join(", ")));
+        }
+        if let Some(limit) = options.limit_value() {
+            req.push_str(&format!(" LIMIT {}", limit));
+        }
+        if let Some(offset) = options.offset_value() {
+            // SQLite only accepts OFFSET after a LIMIT, -1 meaning no limit.
+            if options.limit_value().is_none() {
+                req.push_str(" LIMIT -1");
+            }
+            req.push_str(&format!(" OFFSET {}", offset));
+        }
+        return req;
+    }
+
+    /// Classifies `e`, returned while running `sql`, from its extended result code.
+    fn map_error(e: rusqlite::Error, sql: &str) -> DatabaseError {
+        let (code, message) = match &e {
+            rusqlite::Error::SqliteFailure(err, message) => (Some(*err), message.clone()),
+            _ => (None, None),
+        };
+        // Constraint messages look like "UNIQUE constraint failed: users.id".
+        let target = message.and_then(|m| m.split_once(": ").map(|(_, t)| t.to_owned()));
+        let source = Box::new(e);
+        return match code {
+            Some(err)
+                if err.extended_code == ffi::SQLITE_CONSTRAINT_UNIQUE
+                    || err.extended_code == ffi::SQLITE_CONSTRAINT_PRIMARYKEY =>
+            {
+                DatabaseError::UniqueViolation {
+                    constraint: target,
+                    source,
+                }
+            }
+            Some(err) if err.extended_code == ffi::SQLITE_CONSTRAINT_FOREIGNKEY => {
+                DatabaseError::ForeignKeyViolation {
+                    constraint: target,
+                    source,
+                }
+            }
+            Some(err) if err.extended_code == ffi::SQLITE_CONSTRAINT_NOTNULL => {
+                DatabaseError::NotNullViolation {
+                    column: target.map(|t| match t.rsplit_once('.') {
+                        Some((_, column)) => column.to_owned(),
+                        None => t,
+                    }),
+                    source,
+                }
+            }
+            Some(err)
+                if err.code == ErrorCode::CannotOpen || err.code == ErrorCode::NotADatabase =>
+            {
+                DatabaseError::ConnectionFailed { source }
+            }
+            _ => DatabaseError::QueryFailed {
+                sql: sql.to_owned(),
+                source,
+            },
+        };
+    }
+
+    /// Aggregate of a grouped query, cast to the type of the field it is read
+    /// into when given.
+    fn stringify_aggregate_expression(
+        aggregate: &AggregateExpression,
+        fields: &[DatabaseField],
+        read_as: Option<FieldType>,
+    ) -> Result<String, DatabaseError> {
+        let result_type = aggregate.result_type(fields)?;
+        let expression = format!(
+            "{}({})",
+            Self::get_string_aggregate(aggregate.function()),
+            aggregate.column().unwrap_or_else(|| "*".to_owned())
+        );
+        let read_as = match read_as {
+            Some(FieldType::Null(inner)) => Some(*inner),
+            read_as => read_as,
+        };
+        return Ok(match read_as {
+            Some(read_as) if mem::discriminant(&read_as) != mem::discriminant(&result_type) => {
+                format!("CAST({} AS {})", expression, Self::get_string_type(read_as))
+            }
+            _ => expression,
+        });
+    }
+
+    /// Builds the `SELECT` of a grouped query over `D` read into `R`, pushing
+    /// the condition values in `params`.
+    fn grouped_request<D: DatabaseInsertable, R: DatabaseResult>(
+        query: &GroupedQuery,
+        params: &mut Vec<FieldType>,
+    ) -> Result<String, DatabaseError> {
+        let fields = D::default().fields_value();
+        let result_fields = R::result_fields();
+        query.validate(&fields, &result_fields)?;
+        let mut columns = Vec::new();
+        for field in result_fields.iter() {
+            let name = field.field_name();
+            columns.push(match query.aggregate_for(&name) {
+                Some(aggregate) => format!(
+                    "{} AS {}",
+                    Self::stringify_aggregate_expression(
+                        &aggregate,
+                        &fields,
+                        Some(field.field_type())
+                    )?,
+                    name
+                ),
+                None => name,
+            });
+        }
+        // Aggregates are written again in HAVING, like in Postgres where the
+        // aliases of the SELECT cannot be used.
+        let mut aggregates = Vec::new();
+        for aggregate in query.aggregates() {
+            aggregates.push((
+                aggregate.alias(),
+                Self::stringify_aggregate_expression(&aggregate, &fields, None)?,
+            ));
+        }
+        let having = query.having_conditions().map_columns(&|column| {
+            return match aggregates.iter().find(|(alias, _)| alias == column) {
+                Some((_, expression)) => expression.clone(),
+                None => column.to_owned(),
+            };
+        });
+
+        let mut req = format!("SELECT {} FROM {}", columns.join(", "), D::database_name());
+        req.push_str(&Self::model_conditions::<D>(&query.conditions(), params)?);
+        if !query.group_columns().is_empty() {
+            req.push_str(&format!(" GROUP BY {}", query.group_columns().join(", ")));
+        }
+        if !having.is_empty() {
+            req.push_str(&format!(
+                " HAVING {}",
+                Self::stringify_expression(&having, params)
+            ));
+        }
+        req.push_str(&Self::stringify_options(&query.query_options()));
+        req.push(';');
+        return Ok(req);
+    }
+
+    /// Declaration of the column of `field`, without its constraints.
+    fn column_definition(field: &DatabaseField) -> String {
+        if field.is_auto_increment() {
+            // SQLite only generates keys for an INTEGER PRIMARY KEY column.
+            return format!(
+                "{} INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT",
+                field.field_name()
+            );
+        }
+        let default = match field.default_value() {
+            Some(value) => format!(" DEFAULT {}", Self::stringify_literal(value)),
+            None => String::new(),
+        };
+        let mandatory = match field.is_mandatory() {
+            true => " NOT NULL",
+            false => "",
+        };
+        return format!(
+            "{} {}{}{}",
+            field.field_name(),
+            Self::get_string_type(field.field_type()),
+            default,
+            mandatory
+        );
+    }
+
+    /// Table of `D` created as `table`.
+    fn create_table_request<D: DatabaseInsertable>(table: &str) -> String {
+        let fields = D::default().fields_value();
+        let mut table_fields = Vec::new();
+        let mut list_primary_key = Vec::new();
+        let mut constraints = Vec::new();
+        for field in fields.iter() {
+            let unique = match field.unique() {
+                true => " UNIQUE",
+                false => "",
+            };
+            table_fields.push(format!("{}{}", Self::column_definition(field), unique));
+            if field.is_auto_increment() {
+                continue;
+            }
+            if field.is_primary_key() {
+                list_primary_key.push(field.field_name());
+            }
+            if let Some((foreign_db, foreign_field)) = field.is_foreign_key() {
+                constraints.push(format!(
+                    "FOREIGN KEY ({}) REFERENCES {}({}) ON DELETE SET NULL",
+                    field.field_name(),
+                    foreign_db,
+                    foreign_field
+                ));
+            }
+        }
+        if !list_primary_key.is_empty() {
+            constraints.insert(0, format!("PRIMARY KEY ({})", list_primary_key.join(",")));
+        }
+        table_fields.extend(constraints);
+        return format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n{}\n);",
+            table,
+            table_fields.join(",\n")
+        );
+    }
+
+    /// Schema `D` expects, auto increment keys being declared as INTEGER.
+    fn expected_schema<D: DatabaseInsertable>() -> TableSchema {
+        return TableSchema::of::<D>(|field| {
+            let sql_type = match field.is_auto_increment() {
+                true => "INTEGER",
+                false => Self::get_string_type(field.field_type()),
+            };
+            ColumnSchema::new(&field.field_name(), sql_type, field.is_mandatory())
+        });
+    }
+
+    /// Runs the pragma function query `req` on the `[table, schema]` pair.
+    fn pragma_rows<T>(
+        connection: &Connection,
+        req: &str,
+        params: [&str; 2],
+        read: impl FnMut(&Row) -> rusqlite::Result<T>,
+    ) -> Result<Vec<T>, DatabaseError> {
+        let mut statement = match connection.prepare(req) {
+            Ok(s) => s,
+            Err(e) => return Err(Self::map_error(e, req)),
+        };
+        return match statement
+            .query_map(params, read)
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<T>>>())
+        {
+            Ok(rows) => Ok(rows),
+            Err(e) => Err(Self::map_error(e, req)),
+        };
+    }
+
+    /// Schema of `table` read from its pragmas, `None` when it does not exist.
+    fn table_schema(
+        connection: &Connection,
+        table: &str,
+    ) -> Result<Option<TableSchema>, DatabaseError> {
+        // Reserved names are quoted in SQL only.
+        let table = table.replace('"', "");
+        let params = match table.split_once('.') {
+            Some((schema, name)) => [name, schema],
+            None => [table.as_str(), "main"],
+        };
+        let columns = Self::pragma_rows(
+            connection,
+            "SELECT name, type, \"notnull\", pk FROM pragma_table_info(?1, ?2) ORDER BY cid;",
+            params,
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, bool>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+        if columns.is_empty() {
+            return Ok(None);
+        }
+        let mut schema = TableSchema::new();
+        let mut primary_key = Vec::new();
+        for (name, sql_type, mandatory, position) in columns {
+            if position > 0 {
+                primary_key.push((position, name.clone()));
+            }
+            schema = schema.column(ColumnSchema::new(&name, &sql_type, mandatory));
+        }
+        if !primary_key.is_empty() {
+            primary_key.sort();
+            schema = schema.constraint(TableConstraint::new(
+                ConstraintKind::PrimaryKey,
+                primary_key.into_iter().map(|(_, name)| name).collect(),
+            ));
+        }
+
+        // Fields only declare single column unique and foreign keys, the
+        // others are left alone.
+        let unique = Self::pragma_rows(
+            connection,
+            "SELECT il.name, ii.name, (SELECT COUNT(*) FROM pragma_index_info(il.name, ?2))
+FROM pragma_index_list(?1, ?2) AS il, pragma_index_info(il.name, ?2) AS ii
+WHERE il.origin = 'u' ORDER BY il.name;",
+            params,
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                ))
+            },
+        )?;
+        for (index, column, count) in unique {
+            if count == 1 {
+                schema = schema.constraint(
+                    TableConstraint::new(ConstraintKind::Unique, vec![column]).named(&index),
+                );
+            }
+        }
+        let foreign_keys = Self::pragma_rows(
+            connection,
+            "SELECT \"from\", \"table\", \"to\", COUNT(*) OVER (PARTITION BY id)
+FROM pragma_foreign_key_list(?1, ?2) ORDER BY id;",
+            params,
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            },
+        )?;
+        for (column, table, foreign_column, count) in foreign_keys {
+            if count == 1 {
+                schema = schema.constraint(TableConstraint::new(
+                    ConstraintKind::ForeignKey(table, foreign_column.unwrap_or_default()),
+                    vec![column],
+                ));
+            }
+        }
+        return Ok(Some(schema));
+    }
+
+    /// Statements making `changes` in place, `None` when the table has to be
+    /// rebuilt: SQLite can only add and drop plain columns.
+    fn alter_statements<D: DatabaseInsertable>(changes: &[SchemaChange]) -> Option<Vec<String>> {
+        let table = D::database_name();
+        let fields = D::default().fields_value();
+        let mut statements = Vec::new();
+        for change in changes.iter() {
+            let statement = match change {
+                // A NOT NULL column needs a default to fill the existing rows.
+                SchemaChange::AddColumn(column) => fields
+                    .iter()
+                    .find(|f| f.field_name() == column.name())
+                    .filter(|f| !f.is_mandatory() || f.default_value().is_some())
+                    .map(|f| {
+                        format!(
+                            "ALTER TABLE {} ADD COLUMN {};",
+                            table,
+                            Self::column_definition(f)
+                        )
+                    }),
+                SchemaChange::DropColumn(column) => {
+                    Some(format!("ALTER TABLE {} DROP COLUMN {};", table, column))
+                }
+                _ => None,
+            };
+            match statement {
+                Some(statement) => statements.push(statement),
+                None => return None,
+            }
+        }
+        return Some(statements);
+    }
+
+    /// Statements creating the table of `D` aside, copying the columns it
+    /// keeps from `current`, and swapping the two. Indexes and triggers not
+    /// described by `D` are lost.
+    fn rebuild_statements<D: DatabaseInsertable>(current: &TableSchema) -> Vec<String> {
+        let table = D::database_name();
+        let (schema, name) = match table.split_once('.') {
+            Some((schema, name)) => (format!("{}.", schema), name.to_owned()),
+            None => (String::new(), table.clone()),
+        };
+        let rebuilt = format!("{}_simple_orm_{}", schema, name.replace('"', ""));
+        let kept = D::default()
+            .fields_value()
+            .iter()
+            .map(|f| f.field_name())
+            .filter(|name| current.columns().iter().any(|c| c.name() == *name))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let mut statements = vec![Self::create_table_request::<D>(&rebuilt)];
+        if !kept.is_empty() {
+            statements.push(format!(
+                "INSERT INTO {}({}) SELECT {} FROM {};",
+                rebuilt, kept, kept, table
+            ));
+        }
+        statements.push(format!("DROP TABLE {};", table));
+        statements.push(format!("ALTER TABLE {} RENAME TO {};", rebuilt, name));
+        return statements;
+    }
+
+    fn insert_request<D: DatabaseInsertable>(fields: &[DatabaseField]) -> String {
+        if fields.is_empty() {
+            return format!("INSERT INTO {} DEFAULT VALUES", D::database_name());
+        }
+        let keys = fields
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let placeholders = (1..=fields.len())
+            .map(|idx| format!("?{}", idx))
+            .collect::<Vec<String>>()
+            .join(", ");
+        return format!(
+            "INSERT INTO {}({}) VALUES({})",
+            D::database_name(),
+            keys,
+            placeholders
+        );
+    }
+
+    /// Runs `req`, whose rows hold the columns of `fields` in order, reading
+    /// them as `fields` with the values of the row.
+    fn query_fields(
+        connection: &Connection,
+        req: &str,
+        params: Vec<FieldType>,
+        fields: &[DatabaseField],
+    ) -> Result<Vec<Vec<DatabaseField>>, DatabaseError> {
+        let mut statement = match connection.prepare(req) {
+            Ok(s) => s,
+            Err(e) => return Err(Self::map_error(e, req)),
+        };
+        let rows = statement.query_map(params_from_iter(params), |row| {
+            let mut new_obj_fields: Vec<DatabaseField> = Vec::new();
+            for (idx, field) in fields.iter().enumerate() {
+                let mut new_field = field.clone();
+                new_field.set_field_type(Self::read_field(row, idx, field.field_type())?);
+                new_obj_fields.push(new_field);
+            }
+            Ok(new_obj_fields)
+        });
+        return match rows.and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>()) {
+            Ok(r) => Ok(r),
+            Err(e) => Err(match Self::decoded_column(&e) {
+                Some(idx) => DatabaseError::Decode {
+                    field: Some(fields[idx].field_name()),
+                    source: Box::new(e),
+                },
+                None => Self::map_error(e, req),
+            }),
+        };
+    }
+
+    /// Builds an object of each row read by [`Self::query_fields`].
+    fn from_rows<T>(
+        rows: Vec<Vec<DatabaseField>>,
+        from_fields: impl Fn(Vec<DatabaseField>) -> Result<T, String>,
+    ) -> Result<Vec<T>, DatabaseError> {
+        let mut objects = Vec::new();
+        for new_obj_fields in rows {
+            let obj = match from_fields(new_obj_fields) {
+                Ok(o) => o,
+                Err(e) => {
+                    return Err(DatabaseError::Decode {
+                        field: None,
+                        source: e.into(),
+                    })
+                }
+            };
+            objects.push(obj);
+        }
+        return Ok(objects);
+    }
+
+    /// Runs `req`, whose rows hold the columns of `fields` in order, reading
+    /// each of them as a `D`.
+    async fn query_rows<D: DatabaseInsertable>(
+        &self,
+        req: String,
+        params: Vec<FieldType>,
+        fields: Vec<DatabaseField>,
+    ) -> Result<Vec<D>, DatabaseError> {
+        let rows = self
+            .run(move |connection| Self::query_fields(connection, &req, params, &fields))
+            .await?;
+        return Self::from_rows(rows, D::from_fields);
+    }
+
+    /// Runs `req` and returns the number of rows it changed.
+    async fn execute(&self, req: String, params: Vec<FieldType>) -> Result<u64, DatabaseError> {
+        return self
+            .run(
+                move |connection| match connection.execute(&req, params_from_iter(params)) {
+                    Ok(count) => Ok(count as u64),
+                    Err(e) => Err(Self::map_error(e, &req)),
+                },
+            )
+            .await;
+    }
+
+    /// `None` when `D` has no written column to assign, once the conditions are
+    /// checked.
+    fn update_request<D: DatabaseInsertable>(
+        data: &D,
+        conditions: &ConditionExpression,
+        params: &mut Vec<FieldType>,
+    ) -> Result<Option<String>, DatabaseError> {
+        let fields = data.written_fields();
+        let assignments = fields
+            .iter()
+            .map(|f| {
+                params.push(f.field_type());
+                format!("{} = ?{}", f.field_name(), params.len())
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        let cond = Self::model_conditions::<D>(conditions, params)?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some(format!(
+            "UPDATE {} SET {}{}",
+            D::database_name(),
+            assignments,
+            cond
+        )));
+    }
+
+    fn delete_request<D: DatabaseInsertable>(
+        conditions: &ConditionExpression,
+        params: &mut Vec<FieldType>,
+    ) -> Result<String, DatabaseError> {
+        let cond = Self::model_conditions::<D>(conditions, params)?;
+        return Ok(format!("DELETE FROM {}{}", D::database_name(), cond));
+    }
+
+    fn returning_clause(fields: &[DatabaseField]) -> String {
+        let columns = fields
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>()
+            .join(", ");
+        return format!(" RETURNING {}", columns);
+    }
+
+    /// Index of the column `e` failed to convert, if it is a conversion error.
+    fn decoded_column(e: &rusqlite::Error) -> Option<usize> {
+        return match e {
+            rusqlite::Error::FromSqlConversionFailure(idx, _, _)
+            | rusqlite::Error::IntegralValueOutOfRange(idx, _)
+            | rusqlite::Error::InvalidColumnType(idx, _, _) => Some(*idx),
+            _ => None,
+        };
+    }
+
+    fn read_field(row: &Row, idx: usize, field_type: FieldType) -> rusqlite::Result<FieldType> {
+        let value = match &field_type {
+            FieldType::SmallInt(_) => row.get::<_, Option<i16>>(idx)?.map(FieldType::SmallInt),
+            FieldType::Int(_) => row.get::<_, Option<i32>>(idx)?.map(FieldType::Int),
+            FieldType::BigInt(_) => row.get::<_, Option<i64>>(idx)?.map(FieldType::BigInt),
+            FieldType::UnsignedBigInt(_) => row
+                .get::<_, Option<u64>>(idx)?
+                .map(FieldType::UnsignedBigInt),
+            // SQLite sums fail rather than go over 64 bits.
+            FieldType::HugeInt(_) => row
+                .get::<_, Option<i64>>(idx)?
+                .map(|val| FieldType::HugeInt(val.into())),
+            FieldType::Real(_) => row.get::<_, Option<f32>>(idx)?.map(FieldType::Real),
+            FieldType::Double(_) => row.get::<_, Option<f64>>(idx)?.map(FieldType::Double),
+            FieldType::String(_) => row.get::<_, Option<String>>(idx)?.map(FieldType::String),
+            FieldType::Bool(_) => row.get::<_, Option<bool>>(idx)?.map(FieldType::Bool),
+            FieldType::Null(inner) => return Self::read_field(row, idx, *inner.clone()),
+        };
+        return Ok(value.unwrap_or_else(|| FieldType::Null(Box::new(field_type))));
+    }
+}
+
+#[async_trait]
+impl DatabaseType for SqliteDB {
+    async fn initialize<D: DatabaseInsertable>(&self) -> Result<(), DatabaseError> {
+        let req = Self::create_table_request::<D>(&D::database_name());
+        return self
+            .run(move |connection| match connection.execute_batch(&req) {
+                Ok(()) => Ok(()),
+                Err(e) => Err(Self::map_error(e, &req)),
+            })
+            .await;
+    }
+
+    async fn insert<D: DatabaseInsertable>(&self, data: D) -> Result<(), DatabaseError> {
+        let fields = data.inserted_fields();
+        let req = Self::insert_request::<D>(&fields);
+        let params = fields.iter().map(|f| f.field_type()).collect::<Vec<_>>();
+        return self
+            .run(
+                move |connection| match connection.execute(&req, params_from_iter(params)) {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(Self::map_error(e, &req)),
+                },
+            )
+            .await;
+    }
+
+    async fn insert_many<D: DatabaseInsertable>(&self, data: Vec<D>) -> Result<u64, DatabaseError> {
+        let rows = data.iter().map(|d| d.inserted_fields()).collect::<Vec<_>>();
+        let mut requests = Vec::new();
+        // Rows leaving different columns to their default cannot share a VALUES
+        // list.
+        for group in rows.chunk_by(|a, b| {
+            a.iter()
+                .map(|f| f.field_name())
+                .eq(b.iter().map(|f| f.field_name()))
+        }) {
+            let first = &group[0];
+            if first.is_empty() {
+                // Rows without an inserted column cannot share a VALUES list either.
+                let req = Self::insert_request::<D>(first);
+                for _ in group.iter() {
+                    requests.push((req.clone(), Vec::new()));
+                }
+                continue;
+            }
+            let keys = first
+                .iter()
+                .map(|f| f.field_name())
+                .collect::<Vec<String>>()
+                .join(", ");
+            for chunk in group.chunks(MAX_PARAMETERS / first.len()) {
+                let mut params = Vec::new();
+                let values = chunk
+                    .iter()
+                    .map(|fields| {
+                        let placeholders = fields
+                            .iter()
+                            .map(|f| {
+                                params.push(f.field_type());
+                                format!("?{}", params.len())
+                            })
+                            .collect::<Vec<String>>()
+                            .join(", ");
+                        format!("({})", placeholders)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                let req = format!(
+                    "INSERT INTO {}({}) VALUES {};",
+                    D::database_name(),
+                    keys,
+                    values
+                );
+                requests.push((req, params));
+            }
+        }
+        return self
+            .run(move |connection| {
+                Self::atomic(connection, TransactionBehavior::Deferred, |connection| {
+                    let mut inserted = 0;
+                    for (req, params) in requests {
+                        match connection.execute(&req, params_from_iter(params)) {
+                            Ok(count) => inserted += count as u64,
+                            Err(e) => return Err(Self::map_error(e, &req)),
+                        }
+                    }
+                    return Ok(inserted);
+                })
+            })
+            .await;
+    }
+
+    async fn upsert<D: DatabaseInsertable>(
+        &self,
+        data: D,
+        options: &UpsertOptions,
+    ) -> Result<UpsertOutcome, DatabaseError> {
+        let target = options.conflict_columns::<D>()?;
+        let updated = options.updated_columns::<D>()?;
+        let fields = data.inserted_fields();
+        let action = match updated.is_empty() {
+            true => "DO NOTHING".to_owned(),
+            false => format!(
+                "DO UPDATE SET {}",
+                updated
+                    .iter()
+                    .map(|c| format!("{} = excluded.{}", c, c))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        };
+        let req = format!(
+            "{} ON CONFLICT ({}) {};",
+            Self::insert_request::<D>(&fields),
+            target.join(", "),
+            action
+        );
+        // SQLite does not tell an inserted row from an updated one, so the key is
+        // looked up first, in a transaction holding the write lock.
+        let key_conditions = data
+            .written_fields()
+            .iter()
+            .filter(|f| target.contains(&f.field_name()))
+            .map(|f| {
+                // A key left out of the insert takes its default value.
+                let value = match (f.field_type(), f.default_value()) {
+                    (FieldType::Null(_), Some(default)) => default,
+                    (value, _) => value,
+                };
+                DatabaseCondition::with_value(&f.field_name(), ConditionOperator::Eq, value)
+            })
+            .collect::<Result<Vec<DatabaseCondition>, DatabaseError>>()?;
+        // A generated key is never written, so it cannot conflict.
+        let generated_key = key_conditions.len() < target.len();
+        let key = ConditionExpression::and(key_conditions);
+        let mut key_params = Vec::new();
+        let exists_req = format!(
+            "SELECT EXISTS(SELECT 1 FROM {}{});",
+            D::database_name(),
+            Self::stringify_conditions(&key, &mut key_params)
+        );
+        let params = fields.iter().map(|f| f.field_type()).collect::<Vec<_>>();
+        return self
+            .run(move |connection| {
+                Self::atomic(connection, TransactionBehavior::Immediate, |connection| {
+                    let existed = match generated_key {
+                        true => false,
+                        false => match connection.query_row(
+                            &exists_req,
+                            params_from_iter(key_params),
+                            |row| row.get::<_, bool>(0),
+                        ) {
+                            Ok(existed) => existed,
+                            Err(e) => return Err(Self::map_error(e, &exists_req)),
+                        },
+                    };
+                    let changes = match connection.execute(&req, params_from_iter(params)) {
+                        Ok(changes) => changes,
+                        Err(e) => return Err(Self::map_error(e, &req)),
+                    };
+                    return Ok(match (existed, changes) {
+                        (_, 0) => UpsertOutcome::Unchanged,
+                        (false, _) => UpsertOutcome::Inserted,
+                        (true, _) => UpsertOutcome::Updated,
+                    });
+                })
+            })
+            .await;
+    }
+
+    async fn insert_returning<D: DatabaseInsertable>(&self, data: D) -> Result<D, DatabaseError> {
+        let written = data.inserted_fields();
+        let fields = D::default().fields_value();
+        let req = format!(
+            "{}{};",
+            Self::insert_request::<D>(&written),
+            Self::returning_clause(&fields)
+        );
+        let params = written.iter().map(|f| f.field_type()).collect::<Vec<_>>();
+        let rows = self.query_rows::<D>(req, params, fields).await?;
+        return match rows.into_iter().next() {
+            Some(row) => Ok(row),
+            None => Err(DatabaseError::Decode {
+                field: None,
+                source: "no row returned by the insert".into(),
+            }),
+        };
+    }
+
+    async fn update_affected<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        let mut params = Vec::new();
+        let Some(req) = Self::update_request(data, conditions, &mut params)? else {
+            return Ok(0);
+        };
+        let req = format!("{};", req);
+        return self.execute(req, params).await;
+    }
+
+    async fn update_returning<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError> {
+        let fields = D::default().fields_value();
+        let mut params = Vec::new();
+        let Some(req) = Self::update_request(data, conditions, &mut params)? else {
+            return Ok(Vec::new());
+        };
+        let req = format!("{}{};", req, Self::returning_clause(&fields));
+        return self.query_rows(req, params, fields).await;
+    }
+
+    async fn delete_affected<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        let mut params = Vec::new();
+        let req = format!("{};", Self::delete_request::<D>(conditions, &mut params)?);
+        return self.execute(req, params).await;
+    }
+
+    async fn delete_returning<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError> {
+        let fields = D::default().fields_value();
+        let mut params = Vec::new();
+        let req = format!(
+            "{}{};",
+            Self::delete_request::<D>(conditions, &mut params)?,
+            Self::returning_clause(&fields)
+        );
+        return self.query_rows(req, params, fields).await;
+    }
+
+    async fn get<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+        options: &QueryOptions,
+    ) -> Result<Vec<D>, DatabaseError> {
+        let mut params = Vec::new();
+        let cond = Self::model_conditions::<D>(conditions, &mut params)?;
+        let fields = D::default().fields_value();
+        options.validate(&fields)?;
+        let field_str = fields
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>()
+            .join(", ");
+        let req = format!(
+            "SELECT {} FROM {}{}{};",
+            field_str,
+            D::database_name(),
+            cond,
+            Self::stringify_options(options)
+        );
+        return self.query_rows(req, params, fields).await;
+    }
+
+    async fn count<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        let mut params = Vec::new();
+        let cond = Self::model_conditions::<D>(conditions, &mut params)?;
+        let req = format!("SELECT COUNT(*) FROM {}{};", D::database_name(), cond);
+        return self
+            .run(move |connection| {
+                match connection
+                    .query_row(&req, params_from_iter(params), |row| row.get::<_, u64>(0))
+                {
+                    Ok(count) => Ok(count),
+                    Err(e) => Err(Self::map_error(e, &req)),
+                }
+            })
+            .await;
+    }
+
+    async fn exists<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<bool, DatabaseError> {
+        let mut params = Vec::new();
+        let cond = Self::model_conditions::<D>(conditions, &mut params)?;
+        let req = format!(
+            "SELECT EXISTS(SELECT 1 FROM {}{});",
+            D::database_name(),
+            cond
+        );
+        return self
+            .run(move |connection| {
+                match connection
+                    .query_row(&req, params_from_iter(params), |row| row.get::<_, bool>(0))
+                {
+                    Ok(exists) => Ok(exists),
+                    Err(e) => Err(Self::map_error(e, &req)),
+                }
+            })
+            .await;
+    }
+
+    async fn aggregate<D: DatabaseInsertable>(
+        &self,
+        function: AggregateFunction,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        let result_type = function.column_result_type(column, &D::default().fields_value())?;
+        let mut params = Vec::new();
+        let cond = Self::model_conditions::<D>(conditions, &mut params)?;
+        let req = format!(
+            "SELECT {}({}) FROM {}{};",
+            Self::get_string_aggregate(function),
+            column,
+            D::database_name(),
+            cond
+        );
+        let column = column.to_owned();
+        return self
+            .run(move |connection| {
+                match connection.query_row(&req, params_from_iter(params), |row| {
+                    Self::read_field(row, 0, result_type)
+                }) {
+                    Ok(value) => Ok(value),
+                    Err(e) => Err(match Self::decoded_column(&e) {
+                        Some(_) => DatabaseError::Decode {
+                            field: Some(column),
+                            source: Box::new(e),
+                        },
+                        None => Self::map_error(e, &req),
+                    }),
+                }
+            })
+            .await;
+    }
+
+    async fn get_grouped<D: DatabaseInsertable, R: DatabaseResult>(
+        &self,
+        query: &GroupedQuery,
+    ) -> Result<Vec<R>, DatabaseError> {
+        let mut params = Vec::new();
+        let req = Self::grouped_request::<D, R>(query, &mut params)?;
+        let result_fields = R::result_fields();
+        let rows = self
+            .run(move |connection| Self::query_fields(connection, &req, params, &result_fields))
+            .await?;
+        return Self::from_rows(rows, R::from_fields);
+    }
+
+    async fn diff_schema<D: DatabaseInsertable>(&self) -> Result<SchemaPlan, DatabaseError> {
+        let table = D::database_name();
+        let read_table = table.clone();
+        let current = self
+            .run(move |connection| Self::table_schema(connection, &read_table))
+            .await?;
+        return Ok(match current {
+            Some(current) => {
+                let mut changes = current.changes_to(&Self::expected_schema::<D>());
+                let statements = match Self::alter_statements::<D>(&changes) {
+                    Some(statements) => statements,
+                    None => {
+                        changes.push(SchemaChange::RebuildTable);
+                        Self::rebuild_statements::<D>(&current)
+                    }
+                };
+                SchemaPlan::new(changes, statements)
+            }
+            None => SchemaPlan::new(
+                vec![SchemaChange::CreateTable],
+                vec![Self::create_table_request::<D>(&table)],
+            ),
+        });
+    }
+
+    async fn apply_schema(&self, plan: &SchemaPlan) -> Result<(), DatabaseError> {
+        if plan.is_empty() {
+            return Ok(());
+        }
+        let req = plan.sql();
+        return self
+            .run(move |connection| {
+                Self::atomic(connection, TransactionBehavior::Deferred, |connection| {
+                    return match connection.execute_batch(&req) {
+                        Ok(()) => Ok(()),
+                        Err(e) => Err(Self::map_error(e, &req)),
+                    };
+                })
+            })
+            .await;
+    }
+}
+
+#[async_trait]
+impl DatabaseTransactional for SqliteDB {
+    type Transaction<'a> = SqliteTransaction<'a>;
+
+    /// Starts a deferred transaction, which only locks the database on its
+    /// first statement.
+    async fn begin<'a>(&'a mut self) -> Result<Self::Transaction<'a>, DatabaseError> {
+        self.batch_execute("BEGIN;").await?;
+        return Ok(SqliteTransaction {
+            db: self,
+            finished: false,
+        });
+    }
+
+    /// SQLite transactions are always serializable, so every level is accepted
+    /// and behaves the same.
+    async fn begin_with_isolation<'a>(
+        &'a mut self,
+        _isolation_level: IsolationLevel,
+    ) -> Result<Self::Transaction<'a>, DatabaseError> {
+        return self.begin().await;
+    }
+}
+
+/// Transaction opened with [`DatabaseTransactional::begin`].
+///
+/// Operations are only visible to other connections once [`Self::commit`] is called,
+/// the transaction being rolled back if it is dropped before.
+pub struct SqliteTransaction<'a> {
+    db: &'a SqliteDB,
+    finished: bool,
+}
+
+impl Drop for SqliteTransaction<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.db.rollback_pending.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+#[async_trait]
+impl DatabaseTransaction for SqliteTransaction<'_> {
+    async fn commit(mut self) -> Result<(), DatabaseError> {
+        self.db.batch_execute("COMMIT;").await?;
+        self.finished = true;
+        return Ok(());
+    }
+
+    async fn rollback(mut self) -> Result<(), DatabaseError> {
+        self.db.batch_execute("ROLLBACK;").await?;
+        self.finished = true;
+        return Ok(());
+    }
+}
+
+#[async_trait]
+impl DatabaseType for SqliteTransaction<'_> {
+    async fn initialize<D: DatabaseInsertable>(&self) -> Result<(), DatabaseError> {
+        return self.db.initialize::<D>().await;
+    }
+
+    async fn insert<D: DatabaseInsertable>(&self, data: D) -> Result<(), DatabaseError> {
+        return self.db.insert(data).await;
+    }
+
+    async fn insert_many<D: DatabaseInsertable>(&self, data: Vec<D>) -> Result<u64, DatabaseError> {
+        return self.db.insert_many(data).await;
+    }
+
+    async fn upsert<D: DatabaseInsertable>(
+        &self,
+        data: D,
+        options: &UpsertOptions,
+    ) -> Result<UpsertOutcome, DatabaseError> {
+        return self.db.upsert(data, options).await;
+    }
+
+    async fn insert_returning<D: DatabaseInsertable>(&self, data: D) -> Result<D, DatabaseError> {
+        return self.db.insert_returning(data).await;
+    }
+
+    async fn update_affected<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        return self.db.update_affected(data, conditions).await;
+    }
+
+    async fn update_returning<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError> {
+        return self.db.update_returning(data, conditions).await;
+    }
+
+    async fn delete_affected<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        return self.db.delete_affected::<D>(conditions).await;
+    }
+
+    async fn delete_returning<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError> {
+        return self.db.delete_returning::<D>(conditions).await;
+    }
+
+    async fn get<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+        options: &QueryOptions,
+    ) -> Result<Vec<D>, DatabaseError> {
+        return self.db.get(conditions, options).await;
+    }
+
+    async fn count<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError> {
+        return self.db.count::<D>(conditions).await;
+    }
+
+    async fn exists<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<bool, DatabaseError> {
+        return self.db.exists::<D>(conditions).await;
+    }
+
+    async fn aggregate<D: DatabaseInsertable>(
+        &self,
+        function: AggregateFunction,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        return self.db.aggregate::<D>(function, column, conditions).await;
+    }
+
+    async fn get_grouped<D: DatabaseInsertable, R: DatabaseResult>(
+        &self,
+        query: &GroupedQuery,
+    ) -> Result<Vec<R>, DatabaseError> {
+        return self.db.get_grouped::<D, R>(query).await;
+    }
+
+    async fn diff_schema<D: DatabaseInsertable>(&self) -> Result<SchemaPlan, DatabaseError> {
+        return self.db.diff_schema::<D>().await;
+    }
+
+    async fn apply_schema(&self, plan: &SchemaPlan) -> Result<(), DatabaseError> {
+        return self.db.apply_schema(plan).await;
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+
+    use simple_orm_derive::DatabaseInsertable;
+
+    use crate::models::{
+        database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+        database_error::DatabaseError,
+        database_pagination::{Page, Paginator},
+        database_query::{QueryOptions, SortDirection},
+        database_type::DatabaseType,
+        database_upsert::{UpsertOptions, UpsertOutcome},
+    };
+
+    use super::SqliteDB;
+
+    #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+    #[simple_orm(table = "users")]
+    struct User {
+        #[simple_orm(primary_key)]
+        id: String,
+        name: String,
+        age: u8,
+        activated: bool,
+    }
+
+    fn user(id: &str, age: u8) -> User {
+        return User {
+            id: id.to_owned(),
+            name: "name".to_owned(),
+            age,
+            activated: true,
+        };
+    }
+
+    async fn initialized_db() -> SqliteDB {
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<User>().await.unwrap();
+        return db;
+    }
+
+    #[tokio::test]
+    async fn initialize() {
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        let res = db.initialize::<User>().await;
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test]
+    async fn statements_do_not_block_the_runtime() {
+        let db = initialized_db().await;
+        let connection = Arc::clone(&db.connection);
+        let (locked_sender, locked) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let holder = thread::spawn(move || {
+            let _connection = SqliteDB::lock(&connection);
+            locked_sender.send(()).unwrap();
+            released.recv().unwrap();
+        });
+        locked.recv().unwrap();
+        // The test runtime has a single thread, which would be stuck waiting for
+        // the connection with the release never sent.
+        let releasing = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            release.send(()).unwrap();
+        };
+        let all = ConditionExpression::default();
+        let (count, ()) = tokio::join!(db.count::<User>(&all), releasing);
+        assert_eq!(count.unwrap(), 0);
+        holder.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn insert_and_get() {
+        let db = initialized_db().await;
+        db.insert(user("heyZ", 25)).await.unwrap();
+        db.insert(user("other", 40)).await.unwrap();
+        let conds = ConditionExpression::from(
+            DatabaseCondition::new("id", ConditionOperator::Eq, "heyZ").unwrap(),
+        );
+        let res = db
+            .get::<User>(&conds, &QueryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(res, vec![user("heyZ", 25)]);
+        let conds = ConditionExpression::from(
+            DatabaseCondition::new("age", ConditionOperator::Gte, 25).unwrap(),
+        );
+        assert_eq!(
+            db.get::<User>(&conds, &QueryOptions::default())
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_between_tasks() {
+        let db = std::sync::Arc::new(initialized_db().await);
+        let tasks = (0..8)
+            .map(|idx| {
+                let db = db.clone();
+                tokio::spawn(async move { db.insert(user(&format!("task{}", idx), 20)).await })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        let res = db
+            .get::<User>(&ConditionExpression::default(), &QueryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn insert_many() {
+        let db = initialized_db().await;
+        // More rows than the 8191 a single statement can hold with 4 columns.
+        let users = (0..10000)
+            .map(|idx| user(&format!("user{}", idx), 20))
+            .collect::<Vec<User>>();
+        assert_eq!(db.insert_many(users).await.unwrap(), 10000);
+        let all = ConditionExpression::default();
+        assert_eq!(db.count::<User>(&all).await.unwrap(), 10000);
+        assert_eq!(db.insert_many(Vec::<User>::new()).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn insert_many_rolled_back() {
+        let db = initialized_db().await;
+        let mut users = (0..10000)
+            .map(|idx| user(&format!("user{}", idx), 20))
+            .collect::<Vec<User>>();
+        users.push(user("user0", 30));
+        assert!(matches!(
+            db.insert_many(users).await,
+            Err(DatabaseError::UniqueViolation { .. })
+        ));
+        let all = ConditionExpression::default();
+        assert_eq!(db.count::<User>(&all).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn insert_duplicated_primary_key() {
+        let db = initialized_db().await;
+        db.insert(user("heyZ", 25)).await.unwrap();
+        let res = db.insert(user("heyZ", 26)).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::UniqueViolation { constraint: Some(c), .. } if c == "users.id"
+        ));
+    }
+
+    #[tokio::test]
+    async fn update() {
+        let db = initialized_db().await;
+        db.insert(user("heyZ", 25)).await.unwrap();
+        let conds = ConditionExpression::from(
+            DatabaseCondition::new("id", ConditionOperator::Eq, "heyZ").unwrap(),
+        );
+        db.update::<User>(&user("heyZ", 26), &conds).await.unwrap();
+        let res = db
+            .get::<User>(&conds, &QueryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(res, vec![user("heyZ", 26)]);
+    }
+
+    #[tokio::test]
+    async fn delete() {
+        let db = initialized_db().await;
+        db.insert(user("heyZ", 25)).await.unwrap();
+        db.insert(user("other", 40)).await.unwrap();
+        let conds = ConditionExpression::from(
+            DatabaseCondition::new("id", ConditionOperator::Eq, "heyZ").unwrap(),
+        );
+        db.delete::<User>(&conds).await.unwrap();
+        let res = db
+            .get::<User>(&ConditionExpression::default(), &QueryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(res, vec![user("other", 40)]);
+    }
+
+    #[tokio::test]
+    async fn get_with_nested_conditions() {
+        let db = initialized_db().await;
+        db.insert(user("a", 12)).await.unwrap();
+        db.insert(user("b", 25)).await.unwrap();
+        db.insert(user("c", 40)).await.unwrap();
+        let conds = ConditionExpression::and(vec![
+            ConditionExpression::or(vec![
+                DatabaseCondition::new("id", ConditionOperator::Eq, "a").unwrap(),
+                DatabaseCondition::new("id", ConditionOperator::Eq, "b").unwrap(),
+            ]),
+            ConditionExpression::not(
+                DatabaseCondition::new("age", ConditionOperator::Lt, 18).unwrap(),
+            ),
+        ]);
+        let res = db
+            .get::<User>(&conds, &QueryOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(res, vec![user("b", 25)]);
+        let conds = ConditionExpression::or(Vec::<DatabaseCondition>::new());
+        assert!(db
+            .get::<User>(&conds, &QueryOptions::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    async fn get_ids(db: &SqliteDB, cond: DatabaseCondition) -> Vec<String> {
+        let res = db
+            .get::<User>(&cond.into(), &QueryOptions::default())
+            .await
+            .unwrap();
+        return res.into_iter().map(|u| u.id).collect();
+    }
+
+    async fn db_with_names() -> SqliteDB {
+        let db = initialized_db().await;
+        for (id, name, age) in [("a", "Alice", 12), ("b", "bob", 25), ("c", "Carol", 40)] {
+            let mut new_user = user(id, age);
+            new_user.name = name.to_owned();
+            db.insert(new_user).await.unwrap();
+        }
+        return db;
+    }
+
+    #[tokio::test]
+    async fn get_with_ne() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::new("id", ConditionOperator::Ne, "a").unwrap();
+        assert_eq!(get_ids(&db, cond).await, vec!["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn get_with_in_list() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::in_list("age", vec![12, 40, 99]);
+        assert_eq!(get_ids(&db, cond).await, vec!["a", "c"]);
+        let cond = DatabaseCondition::in_list::<i32>("age", vec![]);
+        assert!(get_ids(&db, cond).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_with_not_in_list() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::not_in_list("id", vec!["a", "b"]);
+        assert_eq!(get_ids(&db, cond).await, vec!["c"]);
+        let cond = DatabaseCondition::not_in_list::<&str>("id", vec![]);
+        assert_eq!(get_ids(&db, cond).await, vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn get_with_like() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::new("name", ConditionOperator::Like, "%o%").unwrap();
+        assert_eq!(get_ids(&db, cond).await, vec!["b", "c"]);
+        let cond = DatabaseCondition::new("name", ConditionOperator::Like, "B%").unwrap();
+        assert!(get_ids(&db, cond).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_with_ilike() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::new("name", ConditionOperator::ILike, "B%").unwrap();
+        assert_eq!(get_ids(&db, cond).await, vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn get_with_between() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::between("age", 12, 25);
+        assert_eq!(get_ids(&db, cond).await, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn get_ordered() {
+        let db = db_with_names().await;
+        let get_ids = |options: QueryOptions| {
+            let db = &db;
+            async move {
+                let res = db
+                    .get::<User>(&ConditionExpression::default(), &options)
+                    .await
+                    .unwrap();
+                res.into_iter().map(|u| u.id).collect::<Vec<String>>()
+            }
+        };
+        let options = QueryOptions::new().order_by("age", SortDirection::Desc);
+        assert_eq!(get_ids(options).await, vec!["c", "b", "a"]);
+        let options = QueryOptions::new()
+            .order_by("activated", SortDirection::Asc)
+            .order_by("name", SortDirection::Asc)
+            .limit(2);
+        assert_eq!(get_ids(options).await, vec!["a", "c"]);
+        let options = QueryOptions::new()
+            .order_by("id", SortDirection::Asc)
+            .limit(1)
+            .offset(1);
+        assert_eq!(get_ids(options).await, vec!["b"]);
+        let options = QueryOptions::new()
+            .order_by("id", SortDirection::Asc)
+            .offset(2);
+        assert_eq!(get_ids(options).await, vec!["c"]);
+    }
+
+    #[tokio::test]
+    async fn get_ordered_by_unknown_column() {
+        let db = db_with_names().await;
+        let options = QueryOptions::new().order_by("id; DROP TABLE users", SortDirection::Asc);
+        let res = db
+            .get::<User>(&ConditionExpression::default(), &options)
+            .await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::UnknownColumn { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn condition_on_unknown_column() {
+        let db = db_with_names().await;
+        let conds = ConditionExpression::from(
+            DatabaseCondition::new("1 = 1 OR id", ConditionOperator::Eq, "a").unwrap(),
+        );
+        let res = db.delete::<User>(&conds).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::UnknownColumn { column } if column == "1 = 1 OR id"
+        ));
+        assert_eq!(
+            db.count::<User>(&ConditionExpression::default())
+                .await
+                .unwrap(),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn upsert() {
+        let db = initialized_db().await;
+        let options = UpsertOptions::new();
+        assert_eq!(
+            db.upsert(user("heyZ", 25), &options).await.unwrap(),
+            UpsertOutcome::Inserted
+        );
+        assert_eq!(
+            db.upsert(user("heyZ", 26), &options).await.unwrap(),
+            UpsertOutcome::Updated
+        );
+        assert_eq!(
+            db.upsert(user("heyZ", 27), &options.clone().do_nothing())
+                .await
+                .unwrap(),
+            UpsertOutcome::Unchanged
+        );
+        let renamed = User {
+            name: "renamed".to_owned(),
+            ..user("heyZ", 28)
+        };
+        assert_eq!(
+            db.upsert(renamed, &options.update_columns(&["name"]))
+                .await
+                .unwrap(),
+            UpsertOutcome::Updated
+        );
+        assert_eq!(
+            db.find::<User, _>("heyZ").await.unwrap(),
+            Some(User {
+                name: "renamed".to_owned(),
+                ..user("heyZ", 26)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn upsert_on_unique_column() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Account {
+            #[simple_orm(primary_key)]
+            id: i32,
+            #[simple_orm(unique)]
+            email: String,
+        }
+
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Account>().await.unwrap();
+        let account = |id: i32| Account {
+            id,
+            email: "me@example.com".to_owned(),
+        };
+        db.insert(account(1)).await.unwrap();
+        let res = db.upsert(account(2), &UpsertOptions::new()).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::UniqueViolation { .. }
+        ));
+        let options = UpsertOptions::new().on_conflict(&["email"]).do_nothing();
+        assert_eq!(
+            db.upsert(account(2), &options).await.unwrap(),
+            UpsertOutcome::Unchanged
+        );
+        assert_eq!(db.find::<Account, _>(1).await.unwrap(), Some(account(1)));
+    }
+
+    #[tokio::test]
+    async fn returning_and_affected_rows() {
+        let db = initialized_db().await;
+        assert_eq!(
+            db.insert_returning(user("heyZ", 25)).await.unwrap(),
+            user("heyZ", 25)
+        );
+        db.insert(user("other", 20)).await.unwrap();
+        db.insert(user("older", 40)).await.unwrap();
+
+        let id = |id: &str| {
+            ConditionExpression::from(
+                DatabaseCondition::new("id", ConditionOperator::Eq, id).unwrap(),
+            )
+        };
+        assert_eq!(
+            db.update_affected(&user("heyZ", 26), &id("heyZ"))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.update_affected(&user("missing", 26), &id("missing"))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            db.update_returning(&user("heyZ", 27), &id("heyZ"))
+                .await
+                .unwrap(),
+            vec![user("heyZ", 27)]
+        );
+
+        let young = ConditionExpression::from(
+            DatabaseCondition::new("age", ConditionOperator::Lt, 30).unwrap(),
+        );
+        let mut deleted = db.delete_returning::<User>(&young).await.unwrap();
+        deleted.sort_by_key(|u| u.age);
+        assert_eq!(deleted, vec![user("other", 20), user("heyZ", 27)]);
+        assert_eq!(db.delete_affected::<User>(&young).await.unwrap(), 0);
+        assert_eq!(
+            db.delete_affected::<User>(&ConditionExpression::default())
+                .await
+                .unwrap(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn auto_increment() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Event {
+            #[simple_orm(primary_key, auto_increment)]
+            id: i64,
+            #[simple_orm(unique)]
+            name: String,
+        }
+
+        let event = |id: i64, name: &str| Event {
+            id,
+            name: name.to_owned(),
+        };
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Event>().await.unwrap();
+        assert_eq!(
+            db.insert_returning(event(0, "launch")).await.unwrap(),
+            event(1, "launch")
+        );
+        db.insert(event(0, "landing")).await.unwrap();
+        assert_eq!(
+            db.insert_many(vec![event(0, "orbit"), event(0, "return")])
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            db.find::<Event, _>(3i64).await.unwrap(),
+            Some(event(3, "orbit"))
+        );
+
+        db.save(&event(3, "orbits")).await.unwrap();
+        assert_eq!(
+            db.find::<Event, _>(3i64).await.unwrap(),
+            Some(event(3, "orbits"))
+        );
+        let options = UpsertOptions::new().on_conflict(&["name"]);
+        assert_eq!(
+            db.upsert(event(0, "splashdown"), &options).await.unwrap(),
+            UpsertOutcome::Inserted
+        );
+        // Nothing but the generated key is left to update.
+        assert_eq!(
+            db.upsert(event(0, "launch"), &options).await.unwrap(),
+            UpsertOutcome::Unchanged
+        );
+        assert_eq!(
+            db.upsert(event(0, "new"), &UpsertOptions::new())
+                .await
+                .unwrap(),
+            UpsertOutcome::Inserted
+        );
+        assert_eq!(
+            db.find::<Event, _>(1i64).await.unwrap(),
+            Some(event(1, "launch"))
+        );
+    }
+
+    #[tokio::test]
+    async fn insert_many_generated_rows() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Ticket {
+            #[simple_orm(primary_key, auto_increment)]
+            id: i64,
+        }
+
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Ticket>().await.unwrap();
+        assert_eq!(
+            db.insert_many(vec![Ticket::default(), Ticket::default()])
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            db.get::<Ticket>(&ConditionExpression::default(), &QueryOptions::new())
+                .await
+                .unwrap(),
+            vec![Ticket { id: 1 }, Ticket { id: 2 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn update_generated_rows() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Ticket {
+            #[simple_orm(primary_key, auto_increment)]
+            id: i64,
+        }
+
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Ticket>().await.unwrap();
+        db.insert(Ticket::default()).await.unwrap();
+        let all = ConditionExpression::default();
+        assert_eq!(
+            db.update_affected(&Ticket { id: 1 }, &all).await.unwrap(),
+            0
+        );
+        assert!(db
+            .update_returning(&Ticket { id: 1 }, &all)
+            .await
+            .unwrap()
+            .is_empty());
+        let unknown = DatabaseCondition::new("missing", ConditionOperator::Eq, 1)
+            .unwrap()
+            .into();
+        assert!(matches!(
+            db.update_affected(&Ticket { id: 1 }, &unknown).await,
+            Err(DatabaseError::UnknownColumn { column }) if column == "missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn reserved_table_name() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "user")]
+        struct Buyer {
+            #[simple_orm(primary_key)]
+            id: String,
+        }
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Order {
+            #[simple_orm(primary_key)]
+            id: i64,
+            #[simple_orm(foreign_key(user, id))]
+            buyer: Option<String>,
+        }
+
+        let order = Order {
+            id: 1,
+            buyer: Some("buyer".to_owned()),
+        };
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Buyer>().await.unwrap();
+        db.initialize::<Order>().await.unwrap();
+        db.insert(Buyer {
+            id: "buyer".to_owned(),
+        })
+        .await
+        .unwrap();
+        db.insert_many(vec![order]).await.unwrap();
+        assert_eq!(
+            db.find::<Order, _>(1i64).await.unwrap(),
+            Some(Order {
+                id: 1,
+                buyer: Some("buyer".to_owned()),
+            })
+        );
+        assert!(db.diff_schema::<Buyer>().await.unwrap().is_empty());
+        assert!(db.diff_schema::<Order>().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn find_save_and_delete_entity() {
+        let db = initialized_db().await;
+        db.insert(user("heyZ", 25)).await.unwrap();
+        assert_eq!(
+            db.find::<User, _>("heyZ").await.unwrap(),
+            Some(user("heyZ", 25))
+        );
+        assert_eq!(db.find::<User, _>("other").await.unwrap(), None);
+
+        db.save(&user("heyZ", 26)).await.unwrap();
+        assert_eq!(
+            db.find::<User, _>("heyZ").await.unwrap(),
+            Some(user("heyZ", 26))
+        );
+        db.delete_entity(&user("heyZ", 26)).await.unwrap();
+        assert_eq!(db.find::<User, _>("heyZ").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn find_with_composite_key() {
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Membership {
+            #[simple_orm(primary_key)]
+            group_id: i32,
+            #[simple_orm(primary_key)]
+            user_id: String,
+            role: String,
+        }
+
+        let membership = |group_id: i32, role: &str| Membership {
+            group_id,
+            user_id: "heyZ".to_owned(),
+            role: role.to_owned(),
+        };
+        let db = SqliteDB::new(":memory:").await.unwrap();
+        db.initialize::<Membership>().await.unwrap();
+        db.insert(membership(1, "member")).await.unwrap();
+        db.insert(membership(2, "member")).await.unwrap();
+        db.save(&membership(2, "admin")).await.unwrap();
+        assert_eq!(
+            db.find::<Membership, _>((1, "heyZ")).await.unwrap(),
+            Some(membership(1, "member"))
+        );
+        assert_eq!(
+            db.find::<Membership, _>((2, "heyZ")).await.unwrap(),
+            Some(membership(2, "admin"))
+        );
+        assert!(matches!(
+            db.find::<Membership, _>(2).await.unwrap_err(),
+            DatabaseError::PrimaryKeyMismatch {
+                expected: 2,
+                found: 1,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn paginate() {
+        let db = initialized_db().await;
+        for (id, age) in [("a", 30), ("b", 20), ("c", 30), ("d", 10), ("e", 20)] {
+            db.insert(user(id, age)).await.unwrap();
+        }
+        let paginator = Paginator::new(2).order_by("age", SortDirection::Desc);
+        let ids = |page: &Page<User>| {
+            page.items()
+                .iter()
+                .map(|u| u.id.clone())
+                .collect::<Vec<String>>()
+        };
+
+        let first = paginator.fetch::<User, _>(&db, None).await.unwrap();
+        assert_eq!(ids(&first), vec!["c", "a"]);
+        assert_eq!(first.previous_cursor(), None);
+        let next = first.next_cursor().unwrap();
+        // Rows sorted before the cursor do not shift the following pages.
+        db.insert(user("f", 40)).await.unwrap();
+        let second = paginator.fetch::<User, _>(&db, Some(&next)).await.unwrap();
+        assert_eq!(ids(&second), vec!["e", "b"]);
+        let last = paginator
+            .fetch::<User, _>(&db, second.next_cursor().as_deref())
+            .await
+            .unwrap();
+        assert_eq!(ids(&last), vec!["d"]);
+        assert_eq!(last.next_cursor(), None);
+
+        let back = paginator
+            .fetch::<User, _>(&db, last.previous_cursor().as_deref())
+            .await
+            .unwrap();
+        assert_eq!(ids(&back), vec!["e", "b"]);
+        let back = paginator
+            .fetch::<User, _>(&db, back.previous_cursor().as_deref())
+            .await
+            .unwrap();
+        assert_eq!(ids(&back), vec!["c", "a"]);
+        let back = paginator
+            .fetch::<User, _>(&db, back.previous_cursor().as_deref())
+            .await
+            .unwrap();
+        assert_eq!(ids(&back), vec!["f"]);
+        assert_eq!(back.previous_cursor(), None);
+    }
+
+    #[tokio::test]
+    async fn paginate_with_invalid_cursor() {
+        let db = initialized_db().await;
+        let res = Paginator::new(2)
+            .fetch::<User, _>(&db, Some("not a cursor"))
+            .await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::InvalidCursor { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn paginate_with_empty_pages() {
+        let db = initialized_db().await;
+        let res = Paginator::new(0).fetch::<User, _>(&db, None).await;
+        assert!(matches!(
+            res.unwrap_err(),
+            DatabaseError::InvalidPageSize { page_size: 0 }
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_with_null_checks() {
+        let db = db_with_names().await;
+        let cond = DatabaseCondition::is_null("name");
+        assert!(get_ids(&db, cond).await.is_empty());
+        let cond = DatabaseCondition::is_not_null("name");
+        assert_eq!(get_ids(&db, cond).await, vec!["a", "b", "c"]);
+    }
+
+    mod aggregates {
+        use simple_orm_derive::{DatabaseInsertable, DatabaseResult};
+
+        use crate::models::{
+            database_aggregate::{AggregateExpression, AggregateFunction},
+            database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+            database_error::DatabaseError,
+            database_field::FieldType,
+            database_query::{GroupedQuery, QueryOptions, SortDirection},
+            database_type::DatabaseType,
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "measures")]
+        struct Measure {
+            #[simple_orm(primary_key)]
+            id: i32,
+            small: i16,
+            big: i64,
+            ratio: f32,
+            flag: bool,
+        }
+
+        fn measure(id: i32, flag: bool) -> Measure {
+            return Measure {
+                id,
+                small: i16::MAX,
+                big: i64::MAX / 2,
+                ratio: 0.5,
+                flag,
+            };
+        }
+
+        #[tokio::test]
+        async fn count_and_exists() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Measure>().await.unwrap();
+            let flagged = ConditionExpression::from(
+                DatabaseCondition::new("flag", ConditionOperator::Eq, true).unwrap(),
+            );
+            assert_eq!(db.count::<Measure>(&flagged).await.unwrap(), 0);
+            assert!(!db.exists::<Measure>(&flagged).await.unwrap());
+            for (id, flag) in [(1, true), (2, false), (3, true)] {
+                db.insert(measure(id, flag)).await.unwrap();
+            }
+            assert_eq!(
+                db.count::<Measure>(&ConditionExpression::default())
+                    .await
+                    .unwrap(),
+                3
+            );
+            assert_eq!(db.count::<Measure>(&flagged).await.unwrap(), 2);
+            assert!(db.exists::<Measure>(&flagged).await.unwrap());
+        }
+
+        #[tokio::test]
+        async fn aggregates() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Measure>().await.unwrap();
+            let all = ConditionExpression::default();
+            assert_eq!(
+                db.max::<Measure>("id", &all).await.unwrap(),
+                FieldType::Null(Box::new(FieldType::Int(0)))
+            );
+            for (id, flag) in [(1, true), (2, false), (3, true)] {
+                db.insert(measure(id, flag)).await.unwrap();
+            }
+            let flagged = ConditionExpression::from(
+                DatabaseCondition::new("flag", ConditionOperator::Eq, true).unwrap(),
+            );
+            assert_eq!(
+                db.sum::<Measure>("small", &all).await.unwrap(),
+                FieldType::BigInt(3 * i16::MAX as i64)
+            );
+            assert_eq!(
+                db.sum::<Measure>("ratio", &all).await.unwrap(),
+                FieldType::Real(1.5)
+            );
+            assert_eq!(
+                db.avg::<Measure>("id", &all).await.unwrap(),
+                FieldType::Double(2.0)
+            );
+            assert_eq!(
+                db.min::<Measure>("flag", &all).await.unwrap(),
+                FieldType::Bool(false)
+            );
+            assert_eq!(
+                db.sum::<Measure>("big", &flagged).await.unwrap(),
+                FieldType::HugeInt((i64::MAX / 2 * 2).into())
+            );
+            // SQLite integers are 64 bits, even for sums.
+            assert!(matches!(
+                db.sum::<Measure>("big", &all).await.unwrap_err(),
+                DatabaseError::QueryFailed { .. }
+            ));
+            assert!(matches!(
+                db.aggregate::<Measure>(AggregateFunction::Sum, "missing", &all)
+                    .await
+                    .unwrap_err(),
+                DatabaseError::UnknownColumn { .. }
+            ));
+        }
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "sales")]
+        struct Sale {
+            #[simple_orm(primary_key)]
+            id: i32,
+            status: String,
+            amount: i32,
+        }
+
+        #[derive(Debug, Default, PartialEq, DatabaseResult)]
+        struct StatusTotal {
+            status: String,
+            sales: i64,
+            total: i64,
+            // Read as REAL rather than the DOUBLE of AVG
+            average: f32,
+        }
+
+        #[tokio::test]
+        async fn grouped() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Sale>().await.unwrap();
+            for (id, status, amount) in [
+                (1, "paid", 10),
+                (2, "paid", 20),
+                (3, "refunded", 5),
+                (4, "pending", 7),
+                (5, "pending", 9),
+                (6, "pending", 2),
+            ] {
+                db.insert(Sale {
+                    id,
+                    status: status.to_owned(),
+                    amount,
+                })
+                .await
+                .unwrap();
+            }
+            let query = GroupedQuery::new()
+                .group_by("status")
+                .aggregate(AggregateExpression::count_all("sales"))
+                .aggregate(AggregateExpression::new(
+                    AggregateFunction::Sum,
+                    "amount",
+                    "total",
+                ))
+                .aggregate(AggregateExpression::new(
+                    AggregateFunction::Avg,
+                    "amount",
+                    "average",
+                ))
+                .filter(ConditionExpression::from(
+                    DatabaseCondition::new("amount", ConditionOperator::Gt, 2).unwrap(),
+                ))
+                .having(ConditionExpression::from(
+                    DatabaseCondition::new("sales", ConditionOperator::Gte, 2).unwrap(),
+                ))
+                .options(QueryOptions::new().order_by("total", SortDirection::Desc));
+            let res = db.get_grouped::<Sale, StatusTotal>(&query).await.unwrap();
+            let status_total = |status: &str, sales, total, average| StatusTotal {
+                status: status.to_owned(),
+                sales,
+                total,
+                average,
+            };
+            assert_eq!(
+                res,
+                vec![
+                    status_total("paid", 2, 30, 15.0),
+                    status_total("pending", 2, 16, 8.0),
+                ]
+            );
+
+            // Every field of the result has to be a group column or an aggregate.
+            let query = GroupedQuery::new()
+                .group_by("status")
+                .aggregate(AggregateExpression::count_all("sales"));
+            assert!(matches!(
+                db.get_grouped::<Sale, StatusTotal>(&query).await.unwrap_err(),
+                DatabaseError::UnknownColumn { column } if column == "total"
+            ));
+        }
+    }
+
+    mod nullable {
+        use simple_orm_derive::DatabaseInsertable;
+
+        use crate::models::{
+            database_condition::{ConditionExpression, DatabaseCondition},
+            database_error::DatabaseError,
+            database_query::{NullsOrder, QueryOptions, SortDirection},
+            database_type::DatabaseType,
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Profile {
+            #[simple_orm(primary_key)]
+            id: String,
+            nickname: Option<String>,
+            age: Option<u8>,
+        }
+
+        /// Same table as [`Profile`] but without nullable fields.
+        mod strict {
+            use simple_orm_derive::DatabaseInsertable;
+
+            #[derive(Debug, Default, DatabaseInsertable)]
+            pub struct Profile {
+                #[simple_orm(primary_key)]
+                id: String,
+                nickname: String,
+                age: u8,
+            }
+        }
+
+        #[tokio::test]
+        async fn get_ordered_with_nulls() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Profile>().await.unwrap();
+            for (id, age) in [("young", Some(20)), ("unknown", None), ("old", Some(60))] {
+                let profile = Profile {
+                    id: id.to_owned(),
+                    nickname: None,
+                    age,
+                };
+                db.insert(profile).await.unwrap();
+            }
+            for (direction, nulls, expected) in [
+                (
+                    SortDirection::Asc,
+                    NullsOrder::First,
+                    ["unknown", "young", "old"],
+                ),
+                (
+                    SortDirection::Asc,
+                    NullsOrder::Last,
+                    ["young", "old", "unknown"],
+                ),
+                (
+                    SortDirection::Desc,
+                    NullsOrder::First,
+                    ["unknown", "old", "young"],
+                ),
+                (
+                    SortDirection::Desc,
+                    NullsOrder::Last,
+                    ["old", "young", "unknown"],
+                ),
+            ] {
+                let options = QueryOptions::new().order_by_nulls("age", direction, nulls);
+                let res = db
+                    .get::<Profile>(&ConditionExpression::default(), &options)
+                    .await
+                    .unwrap();
+                let ids = res.into_iter().map(|p| p.id).collect::<Vec<String>>();
+                assert_eq!(ids, expected);
+            }
+        }
+
+        #[tokio::test]
+        async fn insert_and_get_nullable_fields() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Profile>().await.unwrap();
+            let full = Profile {
+                id: "full".to_owned(),
+                nickname: Some("nick".to_owned()),
+                age: Some(25),
+            };
+            let empty = Profile {
+                id: "empty".to_owned(),
+                nickname: None,
+                age: None,
+            };
+            db.insert(full).await.unwrap();
+            db.insert(empty).await.unwrap();
+            let res = db
+                .get::<Profile>(
+                    &DatabaseCondition::is_null("nickname").into(),
+                    &QueryOptions::default(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                res,
+                vec![Profile {
+                    id: "empty".to_owned(),
+                    nickname: None,
+                    age: None,
+                }]
+            );
+            let res = db
+                .get::<Profile>(
+                    &DatabaseCondition::is_not_null("age").into(),
+                    &QueryOptions::default(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res[0].nickname, Some("nick".to_owned()));
+            assert_eq!(res[0].age, Some(25));
+        }
+
+        #[tokio::test]
+        async fn initialize_not_null_columns() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Profile>().await.unwrap();
+            let connection = SqliteDB::lock(&db.connection);
+            let mut statement = connection
+                .prepare("SELECT name, \"notnull\" FROM pragma_table_info('profile')")
+                .unwrap();
+            let columns = statement
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+                })
+                .unwrap()
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                columns,
+                vec![
+                    ("id".to_owned(), true),
+                    ("nickname".to_owned(), false),
+                    ("age".to_owned(), false)
+                ]
+            );
+        }
+
+        #[tokio::test]
+        async fn get_null_in_mandatory_field() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Profile>().await.unwrap();
+            db.insert(Profile::default()).await.unwrap();
+            let res = db
+                .get::<strict::Profile>(&ConditionExpression::default(), &QueryOptions::default())
+                .await;
+            assert!(matches!(
+                res.unwrap_err(),
+                DatabaseError::Decode { field: None, .. }
+            ));
+        }
+
+        #[tokio::test]
+        async fn insert_null_in_mandatory_field() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<strict::Profile>().await.unwrap();
+            let res = db.insert(Profile::default()).await;
+            assert!(matches!(
+                res.unwrap_err(),
+                DatabaseError::NotNullViolation { column: Some(c), .. } if c == "nickname"
+            ));
+        }
+    }
+
+    mod numeric {
+        use simple_orm_derive::DatabaseInsertable;
+
+        use crate::models::{
+            database_condition::ConditionExpression, database_error::DatabaseError,
+            database_query::QueryOptions, database_type::DatabaseType,
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Numbers {
+            #[simple_orm(primary_key)]
+            id: i64,
+            tiny: i8,
+            small: u8,
+            medium: u16,
+            int: i32,
+            wide: u32,
+            unsigned: u64,
+            real: f32,
+            double: f64,
+        }
+
+        #[tokio::test]
+        async fn numeric_round_trip() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Numbers>().await.unwrap();
+            let numbers = Numbers {
+                id: i64::MIN,
+                tiny: i8::MIN,
+                small: u8::MAX,
+                medium: u16::MAX,
+                int: i32::MIN,
+                wide: u32::MAX,
+                unsigned: i64::MAX as u64,
+                real: 0.1,
+                double: std::f64::consts::PI,
+            };
+            db.insert(numbers).await.unwrap();
+            let res = db
+                .get::<Numbers>(&ConditionExpression::default(), &QueryOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                res,
+                vec![Numbers {
+                    id: i64::MIN,
+                    tiny: i8::MIN,
+                    small: u8::MAX,
+                    medium: u16::MAX,
+                    int: i32::MIN,
+                    wide: u32::MAX,
+                    unsigned: i64::MAX as u64,
+                    real: 0.1,
+                    double: std::f64::consts::PI,
+                }]
+            );
+        }
+
+        #[tokio::test]
+        async fn unsigned_out_of_range() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Numbers>().await.unwrap();
+            let numbers = Numbers {
+                unsigned: u64::MAX,
+                ..Default::default()
+            };
+            let res = db.insert(numbers).await;
+            assert!(matches!(
+                res.unwrap_err(),
+                DatabaseError::QueryFailed { .. }
+            ));
+        }
+    }
+
+    mod attributes {
+        use simple_orm_derive::DatabaseInsertable;
+
+        use crate::models::{
+            database_condition::ConditionExpression,
+            database_query::QueryOptions,
+            database_type::DatabaseType,
+            database_upsert::{UpsertOptions, UpsertOutcome},
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(pluralize)]
+        struct Post {
+            #[simple_orm(primary_key)]
+            id: i64,
+            #[simple_orm(unique, column = "post_title")]
+            title: String,
+            #[simple_orm(default = "draft")]
+            status: String,
+            #[simple_orm(skip)]
+            cached_html: String,
+        }
+
+        #[tokio::test]
+        async fn insert_and_get_with_attributes() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Post>().await.unwrap();
+            let post = Post {
+                id: 1,
+                title: "title".to_owned(),
+                status: "published".to_owned(),
+                cached_html: "<p>title</p>".to_owned(),
+            };
+            db.insert(post).await.unwrap();
+            let duplicated_title = Post {
+                id: 2,
+                title: "title".to_owned(),
+                ..Default::default()
+            };
+            assert!(db.insert(duplicated_title).await.is_err());
+            let res = db
+                .get::<Post>(&ConditionExpression::default(), &QueryOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                res,
+                vec![Post {
+                    id: 1,
+                    title: "title".to_owned(),
+                    status: "published".to_owned(),
+                    cached_html: String::new(),
+                }]
+            );
+        }
+
+        #[tokio::test]
+        async fn initialize_with_default() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Post>().await.unwrap();
+            SqliteDB::lock(&db.connection)
+                .execute("INSERT INTO posts(id, post_title) VALUES(1, 'title')", [])
+                .unwrap();
+            let res = db
+                .get::<Post>(&ConditionExpression::default(), &QueryOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(res[0].status, "draft");
+        }
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        struct Draft {
+            #[simple_orm(primary_key)]
+            id: i64,
+            #[simple_orm(not_null, default = "draft")]
+            status: Option<String>,
+        }
+
+        fn draft(id: i64, status: Option<&str>) -> Draft {
+            return Draft {
+                id,
+                status: status.map(|s| s.to_owned()),
+            };
+        }
+
+        #[tokio::test]
+        async fn insert_missing_value_with_default() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Draft>().await.unwrap();
+            db.insert(draft(1, None)).await.unwrap();
+            assert_eq!(
+                db.insert_many(vec![
+                    draft(2, None),
+                    draft(3, Some("published")),
+                    draft(4, None)
+                ])
+                .await
+                .unwrap(),
+                3
+            );
+            assert_eq!(
+                db.insert_returning(draft(5, None)).await.unwrap(),
+                draft(5, Some("draft"))
+            );
+            assert_eq!(
+                db.upsert(draft(3, None), &UpsertOptions::default())
+                    .await
+                    .unwrap(),
+                UpsertOutcome::Updated
+            );
+            let res = db
+                .get::<Draft>(&ConditionExpression::default(), &QueryOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                res,
+                (1..=5)
+                    .map(|id| draft(id, Some("draft")))
+                    .collect::<Vec<Draft>>()
+            );
+        }
+    }
+
+    mod schema {
+        use simple_orm_derive::DatabaseInsertable;
+
+        use crate::models::{
+            database_condition::ConditionExpression,
+            database_error::DatabaseError,
+            database_query::QueryOptions,
+            database_schema::{ColumnSchema, ConstraintKind, SchemaChange, TableConstraint},
+            database_type::DatabaseType,
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "items")]
+        struct Item {
+            #[simple_orm(primary_key)]
+            id: i64,
+            #[simple_orm(unique)]
+            label: String,
+            score: Option<i64>,
+            #[simple_orm(foreign_key(owners, id))]
+            owner: Option<String>,
+        }
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "events")]
+        struct Event {
+            #[simple_orm(primary_key, auto_increment)]
+            id: i64,
+            name: String,
+        }
+
+        async fn db_with(sql: &str) -> SqliteDB {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            SqliteDB::lock(&db.connection).execute_batch(sql).unwrap();
+            return db;
+        }
+
+        #[tokio::test]
+        async fn diff_missing_and_initialized_tables() {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            let plan = db.diff_schema::<Item>().await.unwrap();
+            assert_eq!(plan.changes(), vec![SchemaChange::CreateTable]);
+            assert!(!plan.is_destructive());
+            db.apply_schema(&plan).await.unwrap();
+            assert!(db.diff_schema::<Item>().await.unwrap().is_empty());
+
+            db.initialize::<Event>().await.unwrap();
+            assert!(db.diff_schema::<Event>().await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn add_column_in_place() {
+            let db = db_with(
+                "CREATE TABLE items (
+id BIGINT NOT NULL PRIMARY KEY,
+label TEXT NOT NULL UNIQUE,
+owner TEXT,
+FOREIGN KEY (owner) REFERENCES owners(id) ON DELETE SET NULL
+);",
+            )
+            .await;
+            let plan = db.diff_schema::<Item>().await.unwrap();
+            assert_eq!(
+                plan.changes(),
+                vec![SchemaChange::AddColumn(ColumnSchema::new(
+                    "score", "BIGINT", false
+                ))]
+            );
+            assert_eq!(
+                plan.statements(),
+                vec!["ALTER TABLE items ADD COLUMN score BIGINT;"]
+            );
+            db.apply_schema(&plan).await.unwrap();
+            assert!(db.diff_schema::<Item>().await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn rebuild_is_destructive() {
+            let db = db_with(
+                "CREATE TABLE owners (id TEXT PRIMARY KEY);
+CREATE TABLE items (
+id BIGINT NOT NULL PRIMARY KEY,
+label TEXT UNIQUE,
+score BIGINT,
+owner TEXT,
+FOREIGN KEY (owner) REFERENCES owners(id) ON DELETE SET NULL
+);
+CREATE INDEX items_score ON items(score);",
+            )
+            .await;
+            let plan = db.diff_schema::<Item>().await.unwrap();
+            assert_eq!(
+                plan.changes(),
+                vec![
+                    SchemaChange::SetNotNull("label".to_owned()),
+                    SchemaChange::RebuildTable,
+                ]
+            );
+            assert_eq!(plan.destructive_changes(), vec![SchemaChange::RebuildTable]);
+            db.apply_schema(&plan).await.unwrap();
+            assert!(db.diff_schema::<Item>().await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn rebuild_table() {
+            let db = db_with(
+                "CREATE TABLE owners (id TEXT PRIMARY KEY);
+CREATE TABLE items (
+id INTEGER NOT NULL PRIMARY KEY,
+label TEXT,
+legacy TEXT UNIQUE,
+score BIGINT
+);
+INSERT INTO items VALUES (1, 'first', 'old', 5);",
+            )
+            .await;
+            let plan = db.diff_schema::<Item>().await.unwrap();
+            assert_eq!(
+                plan.changes(),
+                vec![
+                    SchemaChange::DropConstraint(
+                        TableConstraint::new(ConstraintKind::Unique, vec!["legacy".to_owned()])
+                            .named("sqlite_autoindex_items_1")
+                    ),
+                    SchemaChange::DropColumn("legacy".to_owned()),
+                    SchemaChange::AlterColumnType {
+                        column: "id".to_owned(),
+                        from: "INTEGER".to_owned(),
+                        to: "BIGINT".to_owned(),
+                    },
+                    SchemaChange::AddColumn(ColumnSchema::new("owner", "TEXT", false)),
+                    SchemaChange::SetNotNull("label".to_owned()),
+                    SchemaChange::AddConstraint(TableConstraint::new(
+                        ConstraintKind::Unique,
+                        vec!["label".to_owned()]
+                    )),
+                    SchemaChange::AddConstraint(TableConstraint::new(
+                        ConstraintKind::ForeignKey("owners".to_owned(), "id".to_owned()),
+                        vec!["owner".to_owned()]
+                    )),
+                    SchemaChange::RebuildTable,
+                ]
+            );
+            assert!(plan.is_destructive());
+            assert_eq!(plan.destructive_changes().len(), 4);
+            assert!(plan.sql().contains("DROP TABLE items;"));
+            db.apply_schema(&plan).await.unwrap();
+            assert!(db.diff_schema::<Item>().await.unwrap().is_empty());
+            let items = db
+                .get::<Item>(&ConditionExpression::default(), &QueryOptions::new())
+                .await
+                .unwrap();
+            assert_eq!(
+                items,
+                vec![Item {
+                    id: 1,
+                    label: "first".to_owned(),
+                    score: Some(5),
+                    owner: None,
+                }]
+            );
+        }
+
+        #[tokio::test]
+        async fn apply_rolled_back() {
+            let db = db_with(
+                "CREATE TABLE owners (id TEXT PRIMARY KEY);
+CREATE TABLE items (id BIGINT NOT NULL PRIMARY KEY, label TEXT);
+INSERT INTO items VALUES (1, NULL);",
+            )
+            .await;
+            let plan = db.diff_schema::<Item>().await.unwrap();
+            assert!(matches!(
+                db.apply_schema(&plan).await,
+                Err(DatabaseError::NotNullViolation { column: Some(column), .. })
+                    if column == "label"
+            ));
+            assert_eq!(db.diff_schema::<Item>().await.unwrap(), plan);
+        }
+    }
+
+    mod transactions {
+        use simple_orm_derive::DatabaseInsertable;
+
+        use crate::models::{
+            database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+            database_error::DatabaseError,
+            database_query::QueryOptions,
+            database_transaction::{DatabaseTransaction, DatabaseTransactional, IsolationLevel},
+            database_type::DatabaseType,
+            database_upsert::{UpsertOptions, UpsertOutcome},
+        };
+
+        use super::super::SqliteDB;
+
+        #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+        #[simple_orm(table = "transaction_entries")]
+        struct Entry {
+            #[simple_orm(primary_key)]
+            id: String,
+            amount: i32,
+        }
+
+        fn entry(id: &str, amount: i32) -> Entry {
+            return Entry {
+                id: id.to_owned(),
+                amount,
+            };
+        }
+
+        async fn initialized_db() -> SqliteDB {
+            let db = SqliteDB::new(":memory:").await.unwrap();
+            db.initialize::<Entry>().await.unwrap();
+            return db;
+        }
+
+        async fn all_entries(db: &impl DatabaseType) -> Vec<Entry> {
+            return db
+                .get::<Entry>(&ConditionExpression::default(), &QueryOptions::default())
+                .await
+                .unwrap();
+        }
+
+        #[tokio::test]
+        async fn commit() {
+            let mut db = initialized_db().await;
+            let transaction = db.begin().await.unwrap();
+            transaction.insert(entry("commit", 10)).await.unwrap();
+            assert_eq!(all_entries(&transaction).await, vec![entry("commit", 10)]);
+            transaction.commit().await.unwrap();
+            assert_eq!(all_entries(&db).await, vec![entry("commit", 10)]);
+        }
+
+        #[tokio::test]
+        async fn rollback() {
+            let mut db = initialized_db().await;
+            let transaction = db
+                .begin_with_isolation(IsolationLevel::Serializable)
+                .await
+                .unwrap();
+            transaction.insert(entry("rollback", 10)).await.unwrap();
+            transaction.rollback().await.unwrap();
+            assert!(all_entries(&db).await.is_empty());
+        }
+
+        #[tokio::test]
+        async fn rolled_back_on_drop() {
+            let mut db = initialized_db().await;
+            {
+                let transaction = db.begin().await.unwrap();
+                transaction.insert(entry("dropped", 10)).await.unwrap();
+            }
+            assert!(all_entries(&db).await.is_empty());
+
+            let transaction = db.begin().await.unwrap();
+            transaction.insert(entry("kept", 10)).await.unwrap();
+            transaction.commit().await.unwrap();
+            assert_eq!(all_entries(&db).await, vec![entry("kept", 10)]);
+        }
+
+        #[tokio::test]
+        async fn batches_within_transaction() {
+            let mut db = initialized_db().await;
+            let transaction = db.begin().await.unwrap();
+            transaction.insert(entry("first", 10)).await.unwrap();
+            assert!(matches!(
+                transaction
+                    .insert_many(vec![entry("second", 10), entry("first", 20)])
+                    .await,
+                Err(DatabaseError::UniqueViolation { .. })
+            ));
+            assert_eq!(
+                transaction
+                    .upsert(entry("first", 30), &UpsertOptions::default())
+                    .await
+                    .unwrap(),
+                UpsertOutcome::Updated
+            );
+            transaction
+                .delete::<Entry>(
+                    &DatabaseCondition::new("id", ConditionOperator::Eq, "missing")
+                        .unwrap()
+                        .into(),
+                )
+                .await
+                .unwrap();
+            transaction.commit().await.unwrap();
+            assert_eq!(all_entries(&db).await, vec![entry("first", 30)]);
+        }
+    }
+}
@@ -1,2 +1,9 @@
+#![allow(clippy::needless_return)]
+
+// Lets the derive macro refer to `::simple_orm` from inside this crate.
+extern crate self as simple_orm;
+
 pub mod databases;
 pub mod models;
+
+pub use simple_orm_derive::{DatabaseInsertable, DatabaseResult};
@@ -0,0 +1,169 @@
+use super::{
+    database_error::DatabaseError,
+    database_field::{DatabaseField, FieldType},
+};
+
+/// Function computed over a column by
+/// [`DatabaseType::aggregate`](super::database_type::DatabaseType::aggregate).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    /// Type of the value returned for a column of `field_type`.
+    ///
+    /// Counts are `BigInt`, integer sums are widened to 64 bits, or to a
+    /// `HugeInt` for 64 bits columns, to avoid overflows, averages are always
+    /// `Double`, `Min` and `Max` keep the type of the column.
+    pub fn result_type(&self, field_type: FieldType) -> FieldType {
+        return match (self, field_type) {
+            (_, FieldType::Null(inner)) => self.result_type(*inner),
+            (Self::Count, _) => FieldType::BigInt(0),
+            (Self::Sum, FieldType::SmallInt(_) | FieldType::Int(_)) => FieldType::BigInt(0),
+            (Self::Sum, FieldType::BigInt(_) | FieldType::UnsignedBigInt(_)) => {
+                FieldType::HugeInt(0)
+            }
+            (Self::Avg, _) => FieldType::Double(0.0),
+            (_, field_type) => field_type,
+        };
+    }
+
+    /// [`Self::result_type`] of `column`, which has to be one of `fields`.
+    pub fn column_result_type(
+        &self,
+        column: &str,
+        fields: &[DatabaseField],
+    ) -> Result<FieldType, DatabaseError> {
+        return match fields.iter().find(|f| f.field_name() == column) {
+            Some(field) => Ok(self.result_type(field.field_type())),
+            None => Err(DatabaseError::UnknownColumn {
+                column: column.to_owned(),
+            }),
+        };
+    }
+}
+
+/// Aggregate computed for each group of a
+/// [`GroupedQuery`](super::database_query::GroupedQuery), read into the `alias`
+/// field of the result.
+#[derive(Clone, PartialEq, Debug)]
+pub struct AggregateExpression {
+    function: AggregateFunction,
+    /// `None` for `COUNT(*)`.
+    column: Option<String>,
+    alias: String,
+}
+
+impl AggregateExpression {
+    pub fn new(function: AggregateFunction, column: &str, alias: &str) -> Self {
+        return Self {
+            function,
+            column: Some(column.to_owned()),
+            alias: alias.to_owned(),
+        };
+    }
+
+    /// Number of rows of the group, `NULL` values included.
+    pub fn count_all(alias: &str) -> Self {
+        return Self {
+            function: AggregateFunction::Count,
+            column: None,
+            alias: alias.to_owned(),
+        };
+    }
+
+    pub fn function(&self) -> AggregateFunction {
+        return self.function;
+    }
+
+    pub fn column(&self) -> Option<String> {
+        return self.column.clone();
+    }
+
+    pub fn alias(&self) -> String {
+        return self.alias.clone();
+    }
+
+    /// [`AggregateFunction::result_type`] of the expression over the table with
+    /// `fields`.
+    pub fn result_type(&self, fields: &[DatabaseField]) -> Result<FieldType, DatabaseError> {
+        return match &self.column {
+            Some(column) => self.function.column_result_type(column, fields),
+            None => Ok(FieldType::BigInt(0)),
+        };
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::{
+        database_aggregate::{AggregateExpression, AggregateFunction},
+        database_error::DatabaseError,
+        database_field::{DatabaseField, FieldType},
+    };
+
+    #[test]
+    pub fn result_type() {
+        assert_eq!(
+            AggregateFunction::Sum.result_type(FieldType::SmallInt(0)),
+            FieldType::BigInt(0)
+        );
+        assert_eq!(
+            AggregateFunction::Sum.result_type(FieldType::UnsignedBigInt(0)),
+            FieldType::HugeInt(0)
+        );
+        assert_eq!(
+            AggregateFunction::Sum.result_type(FieldType::Real(0.0)),
+            FieldType::Real(0.0)
+        );
+        assert_eq!(
+            AggregateFunction::Avg.result_type(FieldType::Int(0)),
+            FieldType::Double(0.0)
+        );
+        assert_eq!(
+            AggregateFunction::Count.result_type(FieldType::from("")),
+            FieldType::BigInt(0)
+        );
+        assert_eq!(
+            AggregateFunction::Max.result_type(FieldType::from(None::<i32>)),
+            FieldType::Int(0)
+        );
+    }
+
+    #[test]
+    pub fn column_result_type() {
+        let fields = vec![DatabaseField::new("age", FieldType::from(0u8))];
+        assert_eq!(
+            AggregateFunction::Min
+                .column_result_type("age", &fields)
+                .unwrap(),
+            FieldType::SmallInt(0)
+        );
+        assert!(matches!(
+            AggregateFunction::Min.column_result_type("name", &fields),
+            Err(DatabaseError::UnknownColumn { column }) if column == "name"
+        ));
+    }
+
+    #[test]
+    pub fn expression_result_type() {
+        let fields = vec![DatabaseField::new("ratio", FieldType::from(0.0f32))];
+        assert_eq!(
+            AggregateExpression::count_all("total")
+                .result_type(&fields)
+                .unwrap(),
+            FieldType::BigInt(0)
+        );
+        assert_eq!(
+            AggregateExpression::new(AggregateFunction::Sum, "ratio", "ratios")
+                .result_type(&fields)
+                .unwrap(),
+            FieldType::Real(0.0)
+        );
+    }
+}
@@ -1,29 +1,134 @@
-use super::database_field::FieldType;
+use super::{
+    database_error::DatabaseError,
+    database_field::{DatabaseField, FieldType},
+};
 
 #[derive(Clone, PartialEq, Debug)]
 pub enum ConditionOperator {
     Eq,
+    Ne,
     Gt,
     Gte,
     Lt,
     Lte,
+    In,
+    NotIn,
+    Like,
+    ILike,
+    Between,
+    IsNull,
+    IsNotNull,
 }
 
+/// Operand(s) a [`ConditionOperator`] is applied to.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConditionValue {
+    Empty,
+    Single(FieldType),
+    Range(FieldType, FieldType),
+    Multiple(Vec<FieldType>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub struct DatabaseCondition {
     name: String,
-    value: FieldType,
+    value: ConditionValue,
     operator: ConditionOperator,
 }
 
 impl DatabaseCondition {
-    pub fn new<V: ToString + Clone>(name: &str, operator: ConditionOperator, value: V) -> Self
+    /// Condition comparing the field with a single value.
+    ///
+    /// Fails with [`DatabaseError::InvalidOperator`] if `operator` is `In`,
+    /// `NotIn`, `Between`, `IsNull` or `IsNotNull`, use [`Self::in_list`],
+    /// [`Self::not_in_list`], [`Self::between`], [`Self::is_null`] or
+    /// [`Self::is_not_null`] instead.
+    pub fn new<V: ToString + Clone>(
+        name: &str,
+        operator: ConditionOperator,
+        value: V,
+    ) -> Result<Self, DatabaseError>
+    where
+        FieldType: From<V>,
+    {
+        return Self::with_value(name, operator, FieldType::from(value));
+    }
+
+    /// Same as [`Self::new`] for a value already converted to a [`FieldType`].
+    pub fn with_value(
+        name: &str,
+        operator: ConditionOperator,
+        value: FieldType,
+    ) -> Result<Self, DatabaseError> {
+        if matches!(
+            operator,
+            ConditionOperator::In
+                | ConditionOperator::NotIn
+                | ConditionOperator::Between
+                | ConditionOperator::IsNull
+                | ConditionOperator::IsNotNull
+        ) {
+            return Err(DatabaseError::InvalidOperator {
+                column: name.to_owned(),
+                operator,
+            });
+        }
+        return Ok(Self {
+            name: name.to_owned(),
+            value: ConditionValue::Single(value),
+            operator,
+        });
+    }
+
+    pub fn in_list<V>(name: &str, values: Vec<V>) -> Self
+    where
+        FieldType: From<V>,
+    {
+        return Self::new_multiple(name, ConditionOperator::In, values);
+    }
+
+    pub fn not_in_list<V>(name: &str, values: Vec<V>) -> Self
+    where
+        FieldType: From<V>,
+    {
+        return Self::new_multiple(name, ConditionOperator::NotIn, values);
+    }
+
+    pub fn between<V>(name: &str, low: V, high: V) -> Self
     where
         FieldType: From<V>,
     {
         return Self {
             name: name.to_owned(),
-            value: FieldType::from(value.clone()),
-            operator: operator,
+            value: ConditionValue::Range(FieldType::from(low), FieldType::from(high)),
+            operator: ConditionOperator::Between,
+        };
+    }
+
+    pub fn is_null(name: &str) -> Self {
+        return Self::new_empty(name, ConditionOperator::IsNull);
+    }
+
+    pub fn is_not_null(name: &str) -> Self {
+        return Self::new_empty(name, ConditionOperator::IsNotNull);
+    }
+
+    fn new_multiple<V>(name: &str, operator: ConditionOperator, values: Vec<V>) -> Self
+    where
+        FieldType: From<V>,
+    {
+        return Self {
+            name: name.to_owned(),
+            value: ConditionValue::Multiple(values.into_iter().map(FieldType::from).collect()),
+            operator,
+        };
+    }
+
+    fn new_empty(name: &str, operator: ConditionOperator) -> Self {
+        return Self {
+            name: name.to_owned(),
+            value: ConditionValue::Empty,
+            operator,
         };
     }
 
@@ -35,23 +140,242 @@ impl DatabaseCondition {
         return self.operator.clone();
     }
 
-    pub fn value(&self) -> FieldType {
+    pub fn value(&self) -> ConditionValue {
         return self.value.clone();
     }
 }
 
+/// Boolean combination of [`DatabaseCondition`]s used to filter rows.
+///
+/// An empty `And` matches every row while an empty `Or` matches none.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConditionExpression {
+    Condition(DatabaseCondition),
+    And(Vec<ConditionExpression>),
+    Or(Vec<ConditionExpression>),
+    Not(Box<ConditionExpression>),
+}
+
+impl ConditionExpression {
+    pub fn and<C: Into<ConditionExpression>>(conditions: Vec<C>) -> Self {
+        return Self::And(conditions.into_iter().map(|c| c.into()).collect());
+    }
+
+    pub fn or<C: Into<ConditionExpression>>(conditions: Vec<C>) -> Self {
+        return Self::Or(conditions.into_iter().map(|c| c.into()).collect());
+    }
+
+    pub fn not<C: Into<ConditionExpression>>(condition: C) -> Self {
+        return Self::Not(Box::new(condition.into()));
+    }
+
+    /// Columns the conditions of the expression apply to, in order.
+    pub fn columns(&self) -> Vec<String> {
+        return match self {
+            Self::Condition(cond) => vec![cond.name()],
+            Self::And(conds) | Self::Or(conds) => conds.iter().flat_map(|c| c.columns()).collect(),
+            Self::Not(cond) => cond.columns(),
+        };
+    }
+
+    /// Checks every condition applies to one of `fields`, as their columns are
+    /// written as is in the request.
+    pub fn validate(&self, fields: &[DatabaseField]) -> Result<(), DatabaseError> {
+        for column in self.columns() {
+            if !fields.iter().any(|f| f.field_name() == column) {
+                return Err(DatabaseError::UnknownColumn { column });
+            }
+        }
+        return Ok(());
+    }
+
+    /// Same expression with every column replaced by `rename(column)`.
+    pub fn map_columns<F: Fn(&str) -> String>(&self, rename: &F) -> Self {
+        return match self {
+            Self::Condition(cond) => Self::Condition(DatabaseCondition {
+                name: rename(&cond.name),
+                ..cond.clone()
+            }),
+            Self::And(conds) => Self::And(conds.iter().map(|c| c.map_columns(rename)).collect()),
+            Self::Or(conds) => Self::Or(conds.iter().map(|c| c.map_columns(rename)).collect()),
+            Self::Not(cond) => Self::Not(Box::new(cond.map_columns(rename))),
+        };
+    }
+
+    /// Whether the expression filters nothing and can be left out of the request.
+    pub fn is_empty(&self) -> bool {
+        return match self {
+            Self::And(conditions) => conditions.iter().all(|c| c.is_empty()),
+            _ => false,
+        };
+    }
+}
+
+impl Default for ConditionExpression {
+    fn default() -> Self {
+        return Self::And(Vec::new());
+    }
+}
+
+impl From<DatabaseCondition> for ConditionExpression {
+    fn from(val: DatabaseCondition) -> Self {
+        return Self::Condition(val);
+    }
+}
+impl From<Vec<DatabaseCondition>> for ConditionExpression {
+    fn from(val: Vec<DatabaseCondition>) -> Self {
+        return Self::and(val);
+    }
+}
+impl From<&[DatabaseCondition]> for ConditionExpression {
+    fn from(val: &[DatabaseCondition]) -> Self {
+        return Self::and(val.to_vec());
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::models::{
-        database_condition::{ConditionOperator, DatabaseCondition},
-        database_field::FieldType,
+        database_condition::{
+            ConditionExpression, ConditionOperator, ConditionValue, DatabaseCondition,
+        },
+        database_error::DatabaseError,
+        database_field::{DatabaseField, FieldType},
     };
 
     #[test]
     pub fn new() {
-        let cond = DatabaseCondition::new("id", ConditionOperator::Eq, 32);
+        let cond = DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap();
         assert_eq!(cond.name, "id");
         assert_eq!(cond.operator, ConditionOperator::Eq);
-        assert_eq!(cond.value, FieldType::Number(32));
+        assert_eq!(cond.value, ConditionValue::Single(FieldType::Int(32)));
+    }
+
+    #[test]
+    pub fn new_with_multi_valued_operator() {
+        let res = DatabaseCondition::new("id", ConditionOperator::In, 32);
+        assert!(matches!(
+            res,
+            Err(DatabaseError::InvalidOperator {
+                column,
+                operator: ConditionOperator::In,
+            }) if column == "id"
+        ));
+        let res = DatabaseCondition::with_value("id", ConditionOperator::IsNull, FieldType::Int(1));
+        assert!(matches!(
+            res,
+            Err(DatabaseError::InvalidOperator {
+                operator: ConditionOperator::IsNull,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    pub fn in_list() {
+        let cond = DatabaseCondition::in_list("id", vec![1, 2]);
+        assert_eq!(cond.operator, ConditionOperator::In);
+        assert_eq!(
+            cond.value,
+            ConditionValue::Multiple(vec![FieldType::Int(1), FieldType::Int(2)])
+        );
+        let cond = DatabaseCondition::not_in_list("id", vec!["a"]);
+        assert_eq!(cond.operator, ConditionOperator::NotIn);
+        assert_eq!(
+            cond.value,
+            ConditionValue::Multiple(vec![FieldType::from("a")])
+        );
+    }
+
+    #[test]
+    pub fn between() {
+        let cond = DatabaseCondition::between("age", 18, 30);
+        assert_eq!(cond.operator, ConditionOperator::Between);
+        assert_eq!(
+            cond.value,
+            ConditionValue::Range(FieldType::Int(18), FieldType::Int(30))
+        );
+    }
+
+    #[test]
+    pub fn null_checks() {
+        let cond = DatabaseCondition::is_null("name");
+        assert_eq!(cond.operator, ConditionOperator::IsNull);
+        assert_eq!(cond.value, ConditionValue::Empty);
+        let cond = DatabaseCondition::is_not_null("name");
+        assert_eq!(cond.operator, ConditionOperator::IsNotNull);
+        assert_eq!(cond.value, ConditionValue::Empty);
+    }
+
+    #[test]
+    pub fn expression_from_conditions() {
+        let conds = vec![
+            DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap(),
+            DatabaseCondition::new("age", ConditionOperator::Gt, 18).unwrap(),
+        ];
+        let expr = ConditionExpression::from(conds.clone());
+        assert_eq!(
+            expr,
+            ConditionExpression::And(vec![
+                ConditionExpression::Condition(conds[0].clone()),
+                ConditionExpression::Condition(conds[1].clone()),
+            ])
+        );
+    }
+
+    #[test]
+    pub fn expression_is_empty() {
+        let cond = DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap();
+        assert!(ConditionExpression::default().is_empty());
+        assert!(ConditionExpression::and(vec![ConditionExpression::default()]).is_empty());
+        assert!(!ConditionExpression::from(cond.clone()).is_empty());
+        assert!(!ConditionExpression::or(Vec::<DatabaseCondition>::new()).is_empty());
+        assert!(!ConditionExpression::not(ConditionExpression::default()).is_empty());
+    }
+
+    #[test]
+    pub fn expression_columns() {
+        let expr = ConditionExpression::and(vec![
+            ConditionExpression::from(
+                DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap(),
+            ),
+            ConditionExpression::not(DatabaseCondition::is_null("name")),
+        ]);
+        assert_eq!(expr.columns(), vec!["id", "name"]);
+        let renamed = expr.map_columns(&|column| format!("t.{}", column));
+        assert_eq!(
+            renamed,
+            ConditionExpression::and(vec![
+                ConditionExpression::from(
+                    DatabaseCondition::new("t.id", ConditionOperator::Eq, 32).unwrap()
+                ),
+                ConditionExpression::not(DatabaseCondition::is_null("t.name")),
+            ])
+        );
+    }
+
+    #[test]
+    pub fn expression_validate() {
+        let fields = vec![
+            DatabaseField::new("id", FieldType::from(0)),
+            DatabaseField::new("name", FieldType::from("")),
+        ];
+        let expr = ConditionExpression::and(vec![
+            ConditionExpression::from(
+                DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap(),
+            ),
+            ConditionExpression::not(DatabaseCondition::is_null("name")),
+        ]);
+        assert!(expr.validate(&fields).is_ok());
+        let expr = ConditionExpression::or(vec![
+            ConditionExpression::from(
+                DatabaseCondition::new("id", ConditionOperator::Eq, 32).unwrap(),
+            ),
+            ConditionExpression::from(DatabaseCondition::is_null("1 = 1 OR name")),
+        ]);
+        assert!(matches!(
+            expr.validate(&fields),
+            Err(DatabaseError::UnknownColumn { column }) if column == "1 = 1 OR name"
+        ));
     }
 }
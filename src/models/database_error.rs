@@ -1,5 +1,191 @@
+use std::{error::Error, fmt};
+
+use super::database_condition::ConditionOperator;
+
+/// Error returned by the driver the [`DatabaseError`] comes from.
+pub type DriverError = Box<dyn Error + Send + Sync>;
+
 #[derive(Debug)]
-pub struct DatabaseError {
-    pub error: String,
-    pub details: String,
+pub enum DatabaseError {
+    /// The connection to the database could not be opened or was lost.
+    ConnectionFailed { source: DriverError },
+    /// No pooled connection became available before the acquire timeout.
+    AcquireTimeout,
+    /// A row with the same value already exists for a primary key or unique column.
+    UniqueViolation {
+        constraint: Option<String>,
+        source: DriverError,
+    },
+    /// A foreign key references a row which does not exist, or is still referenced.
+    ForeignKeyViolation {
+        constraint: Option<String>,
+        source: DriverError,
+    },
+    /// `NULL` was saved in a `NOT NULL` column.
+    NotNullViolation {
+        column: Option<String>,
+        source: DriverError,
+    },
+    /// The transaction conflicted with a concurrent one and can be retried.
+    SerializationFailure { source: DriverError },
+    /// A value read from the database does not fit its field, `field` being unknown
+    /// when the whole row was rejected.
+    Decode {
+        field: Option<String>,
+        source: DriverError,
+    },
+    /// A condition on `column` was built with an operator which does not take a
+    /// single value.
+    InvalidOperator {
+        column: String,
+        operator: ConditionOperator,
+    },
+    /// A column used to build the request is not a field of the struct.
+    UnknownColumn { column: String },
+    /// A column cannot be overwritten, being generated by the database or the
+    /// conflict target of an upsert.
+    UnwritableColumn { column: String },
+    /// The struct has no field flagged as primary key, which the operation needs.
+    MissingPrimaryKey { table: String },
+    /// A primary key was given with `found` values for the `expected` primary key
+    /// fields of the struct.
+    PrimaryKeyMismatch {
+        table: String,
+        expected: usize,
+        found: usize,
+    },
+    /// A pagination cursor was not produced by the same paginator.
+    InvalidCursor { cursor: String },
+    /// A paginator was given a page size of 0, or too large for a SQL limit.
+    InvalidPageSize { page_size: u64 },
+    /// Two migrations were given the same version.
+    DuplicateMigration { version: i64 },
+    /// An applied migration is not among the known ones.
+    MissingMigration { version: i64 },
+    /// An applied migration was modified since.
+    MigrationChecksumMismatch { version: i64 },
+    /// A migration to revert has no down step.
+    IrreversibleMigration { version: i64 },
+    /// Any other failure of the `sql` request.
+    QueryFailed { sql: String, source: DriverError },
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            Self::ConnectionFailed { .. } => write!(f, "cannot connect to database"),
+            Self::AcquireTimeout => write!(f, "timed out waiting for a pooled connection"),
+            Self::UniqueViolation {
+                constraint: Some(constraint),
+                ..
+            } => write!(f, "unique constraint {} violated", constraint),
+            Self::UniqueViolation { .. } => write!(f, "unique constraint violated"),
+            Self::ForeignKeyViolation {
+                constraint: Some(constraint),
+                ..
+            } => write!(f, "foreign key constraint {} violated", constraint),
+            Self::ForeignKeyViolation { .. } => write!(f, "foreign key constraint violated"),
+            Self::NotNullViolation {
+                column: Some(column),
+                ..
+            } => write!(f, "null value in column {}", column),
+            Self::NotNullViolation { .. } => write!(f, "null value in not null column"),
+            Self::SerializationFailure { .. } => {
+                write!(f, "transaction conflicted with a concurrent one")
+            }
+            Self::Decode {
+                field: Some(field), ..
+            } => write!(f, "cannot decode field {}", field),
+            Self::Decode { .. } => write!(f, "cannot decode row"),
+            Self::InvalidOperator { column, operator } => write!(
+                f,
+                "operator {:?} on column {} does not take a single value",
+                operator, column
+            ),
+            Self::UnknownColumn { column } => write!(f, "unknown column {}", column),
+            Self::UnwritableColumn { column } => write!(f, "column {} cannot be updated", column),
+            Self::MissingPrimaryKey { table } => write!(f, "table {} has no primary key", table),
+            Self::PrimaryKeyMismatch {
+                table,
+                expected,
+                found,
+            } => write!(
+                f,
+                "table {} has {} primary key columns, {} values given",
+                table, expected, found
+            ),
+            Self::InvalidCursor { cursor } => write!(f, "invalid cursor {}", cursor),
+            Self::InvalidPageSize { page_size } => write!(f, "invalid page size {}", page_size),
+            Self::DuplicateMigration { version } => {
+                write!(f, "several migrations have version {}", version)
+            }
+            Self::MissingMigration { version } => {
+                write!(f, "applied migration {} is unknown", version)
+            }
+            Self::MigrationChecksumMismatch { version } => {
+                write!(f, "migration {} was modified after being applied", version)
+            }
+            Self::IrreversibleMigration { version } => {
+                write!(f, "migration {} cannot be reverted", version)
+            }
+            Self::QueryFailed { sql, .. } => write!(f, "query failed: {}", sql),
+        };
+    }
+}
+
+impl Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        let source = match self {
+            Self::ConnectionFailed { source }
+            | Self::UniqueViolation { source, .. }
+            | Self::ForeignKeyViolation { source, .. }
+            | Self::NotNullViolation { source, .. }
+            | Self::SerializationFailure { source }
+            | Self::Decode { source, .. }
+            | Self::QueryFailed { source, .. } => source,
+            Self::AcquireTimeout
+            | Self::InvalidOperator { .. }
+            | Self::UnknownColumn { .. }
+            | Self::UnwritableColumn { .. }
+            | Self::MissingPrimaryKey { .. }
+            | Self::PrimaryKeyMismatch { .. }
+            | Self::InvalidCursor { .. }
+            | Self::InvalidPageSize { .. }
+            | Self::DuplicateMigration { .. }
+            | Self::MissingMigration { .. }
+            | Self::MigrationChecksumMismatch { .. }
+            | Self::IrreversibleMigration { .. } => return None,
+        };
+        return Some(source.as_ref());
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::error::Error;
+
+    use crate::models::database_error::DatabaseError;
+
+    #[test]
+    pub fn display() {
+        let err = DatabaseError::UniqueViolation {
+            constraint: Some("users_pkey".to_owned()),
+            source: "duplicate key".into(),
+        };
+        assert_eq!(err.to_string(), "unique constraint users_pkey violated");
+        let err = DatabaseError::Decode {
+            field: None,
+            source: "Field 'id' not found in fields vector".into(),
+        };
+        assert_eq!(err.to_string(), "cannot decode row");
+    }
+
+    #[test]
+    pub fn source() {
+        let err = DatabaseError::QueryFailed {
+            sql: "SELECT 1".to_owned(),
+            source: "syntax error".into(),
+        };
+        assert_eq!(err.source().unwrap().to_string(), "syntax error");
+    }
 }
@@ -1,10 +1,19 @@
-use super::database_insertable::DatabaseInsertable;
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FieldType {
-    Number(i64),
+    SmallInt(i16),
+    Int(i32),
+    BigInt(i64),
+    /// `u64` does not fit in any signed SQL integer, so it gets its own variant.
+    UnsignedBigInt(u64),
+    /// Integer wider than 64 bits, only returned by aggregates like the sum of
+    /// a `BigInt` column.
+    HugeInt(i128),
+    Real(f32),
+    Double(f64),
     String(String),
     Bool(bool),
+    /// SQL NULL, the boxed value only describes the type of the column.
+    Null(Box<FieldType>),
 }
 
 impl Default for FieldType {
@@ -13,6 +22,20 @@ impl Default for FieldType {
     }
 }
 
+impl FieldType {
+    /// Value of any of the integer variants, whatever its width.
+    pub fn integer_value(&self) -> Option<i128> {
+        return match self {
+            Self::SmallInt(val) => Some((*val).into()),
+            Self::Int(val) => Some((*val).into()),
+            Self::BigInt(val) => Some((*val).into()),
+            Self::UnsignedBigInt(val) => Some((*val).into()),
+            Self::HugeInt(val) => Some(*val),
+            _ => None,
+        };
+    }
+}
+
 impl From<String> for FieldType {
     fn from(val: String) -> Self {
         return Self::String(val);
@@ -25,22 +48,52 @@ impl From<&str> for FieldType {
 }
 impl From<u8> for FieldType {
     fn from(val: u8) -> Self {
-        return Self::Number(val.into());
+        return Self::SmallInt(val.into());
     }
 }
 impl From<i8> for FieldType {
     fn from(val: i8) -> Self {
-        return Self::Number(val.into());
+        return Self::SmallInt(val.into());
     }
 }
 impl From<i16> for FieldType {
     fn from(val: i16) -> Self {
-        return Self::Number(val.into());
+        return Self::SmallInt(val);
+    }
+}
+impl From<u16> for FieldType {
+    fn from(val: u16) -> Self {
+        return Self::Int(val.into());
     }
 }
 impl From<i32> for FieldType {
     fn from(val: i32) -> Self {
-        return Self::Number(val.into());
+        return Self::Int(val);
+    }
+}
+impl From<u32> for FieldType {
+    fn from(val: u32) -> Self {
+        return Self::BigInt(val.into());
+    }
+}
+impl From<i64> for FieldType {
+    fn from(val: i64) -> Self {
+        return Self::BigInt(val);
+    }
+}
+impl From<u64> for FieldType {
+    fn from(val: u64) -> Self {
+        return Self::UnsignedBigInt(val);
+    }
+}
+impl From<f32> for FieldType {
+    fn from(val: f32) -> Self {
+        return Self::Real(val);
+    }
+}
+impl From<f64> for FieldType {
+    fn from(val: f64) -> Self {
+        return Self::Double(val);
     }
 }
 impl From<bool> for FieldType {
@@ -49,14 +102,28 @@ impl From<bool> for FieldType {
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+impl<T: Default> From<Option<T>> for FieldType
+where
+    FieldType: From<T>,
+{
+    fn from(val: Option<T>) -> Self {
+        return match val {
+            Some(val) => Self::from(val),
+            None => Self::Null(Box::new(Self::from(T::default()))),
+        };
+    }
+}
+
+#[derive(PartialEq, Clone)]
 pub struct DatabaseField {
     field_name: String,
     field_type: FieldType,
     unique: bool,
     mandatory: bool,
     primary_key: bool,
+    auto_increment: bool,
     foreign_key: Option<(String, String)>,
+    default: Option<FieldType>,
 }
 
 impl DatabaseField {
@@ -67,7 +134,9 @@ impl DatabaseField {
             unique: false,
             mandatory: false,
             primary_key: false,
+            auto_increment: false,
             foreign_key: None,
+            default: None,
         };
     }
     pub fn builder(field_name: &str, field_type: FieldType) -> DatabaseFieldBuilder {
@@ -91,9 +160,17 @@ impl DatabaseField {
     pub fn is_primary_key(&self) -> bool {
         return self.primary_key;
     }
+    /// Generated by the database, and never written by the ORM.
+    pub fn is_auto_increment(&self) -> bool {
+        return self.auto_increment;
+    }
     pub fn is_foreign_key(&self) -> Option<(String, String)> {
         return self.foreign_key.clone();
     }
+    /// Value the column takes in database when none is given.
+    pub fn default_value(&self) -> Option<FieldType> {
+        return self.default.clone();
+    }
 }
 
 pub struct DatabaseFieldBuilder {
@@ -112,6 +189,10 @@ impl DatabaseFieldBuilder {
         self.dbf.primary_key = true;
         return self;
     }
+    pub fn is_auto_increment(mut self) -> Self {
+        self.dbf.auto_increment = true;
+        return self;
+    }
     pub fn is_foreign_key(mut self, foreign_db: &str, foreign_field: &str) -> Self {
         self.dbf.foreign_key = Some((foreign_db.to_owned(), foreign_field.to_owned()));
         return self;
@@ -120,7 +201,61 @@ impl DatabaseFieldBuilder {
         self.dbf.unique = true;
         return self;
     }
+    pub fn has_default(mut self, default: FieldType) -> Self {
+        self.dbf.default = Some(default);
+        return self;
+    }
     pub fn build(self) -> DatabaseField {
         return self.dbf;
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::database_field::FieldType;
+
+    #[test]
+    pub fn integer_widths() {
+        assert_eq!(FieldType::from(1u8), FieldType::SmallInt(1));
+        assert_eq!(FieldType::from(1i8), FieldType::SmallInt(1));
+        assert_eq!(FieldType::from(1i16), FieldType::SmallInt(1));
+        assert_eq!(FieldType::from(u16::MAX), FieldType::Int(65535));
+        assert_eq!(FieldType::from(1i32), FieldType::Int(1));
+        assert_eq!(FieldType::from(u32::MAX), FieldType::BigInt(4294967295));
+        assert_eq!(FieldType::from(1i64), FieldType::BigInt(1));
+        assert_eq!(
+            FieldType::from(u64::MAX),
+            FieldType::UnsignedBigInt(u64::MAX)
+        );
+    }
+
+    #[test]
+    pub fn float_widths() {
+        assert_eq!(FieldType::from(0.5f32), FieldType::Real(0.5));
+        assert_eq!(FieldType::from(0.5f64), FieldType::Double(0.5));
+    }
+
+    #[test]
+    pub fn integer_value() {
+        assert_eq!(FieldType::SmallInt(-1).integer_value(), Some(-1));
+        assert_eq!(
+            FieldType::UnsignedBigInt(u64::MAX).integer_value(),
+            Some(u64::MAX.into())
+        );
+        assert_eq!(
+            FieldType::HugeInt(i128::MIN).integer_value(),
+            Some(i128::MIN)
+        );
+        assert_eq!(FieldType::Real(1.0).integer_value(), None);
+        assert_eq!(FieldType::from("1").integer_value(), None);
+    }
+
+    #[test]
+    pub fn null_from_option() {
+        assert_eq!(FieldType::from(Some(3u8)), FieldType::SmallInt(3));
+        assert_eq!(
+            FieldType::from(None::<u8>),
+            FieldType::Null(Box::new(FieldType::SmallInt(0)))
+        );
+    }
+}
@@ -1,12 +1,236 @@
-use super::database_field::DatabaseField;
+use super::database_field::{DatabaseField, FieldType};
 
 pub trait DatabaseInsertable: Send + Default {
     fn database_name() -> String
     where
         Self: Sized;
     fn fields_value(&self) -> Vec<DatabaseField>;
+    /// [`Self::fields_value`] without the columns generated by the database,
+    /// which inserts and updates leave out.
+    fn written_fields(&self) -> Vec<DatabaseField> {
+        return self
+            .fields_value()
+            .into_iter()
+            .filter(|f| !f.is_auto_increment())
+            .collect();
+    }
+    /// [`Self::written_fields`] without the missing values of columns having a
+    /// default, which inserts leave to the database.
+    fn inserted_fields(&self) -> Vec<DatabaseField> {
+        return self
+            .written_fields()
+            .into_iter()
+            .filter(|f| {
+                !(matches!(f.field_type(), FieldType::Null(_)) && f.default_value().is_some())
+            })
+            .collect();
+    }
 
     fn from_fields(fields: Vec<DatabaseField>) -> Result<Self, String>
     where
         Self: Sized;
 }
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::{
+        database_field::{DatabaseField, FieldType},
+        database_insertable::DatabaseInsertable,
+    };
+    use simple_orm_derive::DatabaseInsertable;
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct UserProfile {
+        id: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct HTTPServer {
+        id: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    #[simple_orm(pluralize)]
+    pub struct Category {
+        id: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    #[simple_orm(table = "app.accounts")]
+    pub struct Account {
+        id: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct User {
+        id: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    #[simple_orm(table = "app.Order")]
+    pub struct Order {
+        #[simple_orm(foreign_key(user, id))]
+        buyer: String,
+    }
+
+    mod reexport {
+        pub use crate as orm;
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    #[simple_orm(crate = "reexport::orm")]
+    pub struct Setting {
+        id: String,
+    }
+
+    #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+    pub struct Post {
+        #[simple_orm(primary_key)]
+        pub id: i64,
+        #[simple_orm(unique, column = "post_title")]
+        pub title: String,
+        #[simple_orm(not_null, default = "draft")]
+        pub status: Option<String>,
+        #[simple_orm(foreign_key(users, id))]
+        pub author: String,
+        #[simple_orm(skip)]
+        pub cached_html: String,
+    }
+
+    #[test]
+    pub fn field_attributes() {
+        let post = Post {
+            id: 1,
+            title: "title".to_owned(),
+            status: None,
+            author: "me".to_owned(),
+            cached_html: "<p></p>".to_owned(),
+        };
+        let fields = post.fields_value();
+        let names = fields
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["id", "post_title", "status", "author"]);
+
+        assert!(fields[0].is_primary_key());
+        assert!(fields[0].is_mandatory());
+        assert!(fields[1].unique());
+        assert!(!fields[1].is_primary_key());
+        assert!(fields[2].is_mandatory());
+        assert_eq!(fields[2].default_value(), Some(FieldType::from("draft")));
+        assert_eq!(
+            fields[3].is_foreign_key(),
+            Some(("users".to_owned(), "id".to_owned()))
+        );
+        assert_eq!(fields[3].default_value(), None);
+    }
+
+    #[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+    pub struct Event {
+        #[simple_orm(primary_key, auto_increment)]
+        pub id: i64,
+        pub name: String,
+    }
+
+    #[test]
+    pub fn auto_increment() {
+        let event = Event {
+            id: 0,
+            name: "launch".to_owned(),
+        };
+        let fields = event.fields_value();
+        assert!(fields[0].is_auto_increment());
+        assert!(fields[0].is_primary_key());
+        assert!(!fields[1].is_auto_increment());
+        let written = event
+            .written_fields()
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>();
+        assert_eq!(written, vec!["name"]);
+    }
+
+    #[test]
+    pub fn inserted_fields() {
+        let post = Post {
+            id: 1,
+            title: "title".to_owned(),
+            status: None,
+            author: "me".to_owned(),
+            cached_html: String::new(),
+        };
+        let names = |fields: Vec<DatabaseField>| {
+            fields
+                .iter()
+                .map(|f| f.field_name())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            names(post.inserted_fields()),
+            vec!["id", "post_title", "author"]
+        );
+        assert_eq!(
+            names(post.written_fields()),
+            vec!["id", "post_title", "status", "author"]
+        );
+        let published = Post {
+            status: Some("published".to_owned()),
+            ..post
+        };
+        assert_eq!(
+            names(published.inserted_fields()),
+            vec!["id", "post_title", "status", "author"]
+        );
+    }
+
+    #[test]
+    pub fn from_fields_with_attributes() {
+        let post = Post {
+            id: 1,
+            title: "title".to_owned(),
+            status: Some("published".to_owned()),
+            author: "me".to_owned(),
+            cached_html: "<p></p>".to_owned(),
+        };
+        let res = Post::from_fields(post.fields_value()).unwrap();
+        assert_eq!(
+            res,
+            Post {
+                cached_html: String::new(),
+                ..post
+            }
+        );
+    }
+
+    #[test]
+    pub fn default_table_name() {
+        assert_eq!(UserProfile::database_name(), "user_profile");
+        assert_eq!(HTTPServer::database_name(), "http_server");
+    }
+
+    #[test]
+    pub fn pluralized_table_name() {
+        assert_eq!(Category::database_name(), "categories");
+    }
+
+    #[test]
+    pub fn explicit_table_name() {
+        assert_eq!(Account::database_name(), "app.accounts");
+    }
+
+    #[test]
+    pub fn reserved_table_name() {
+        assert_eq!(User::database_name(), "\"user\"");
+        assert_eq!(Order::database_name(), "app.\"order\"");
+        assert_eq!(
+            Order::default().fields_value()[0].is_foreign_key(),
+            Some(("\"user\"".to_owned(), "id".to_owned()))
+        );
+    }
+
+    #[test]
+    pub fn crate_path_override() {
+        assert_eq!(Setting::database_name(), "setting");
+    }
+}
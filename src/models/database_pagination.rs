@@ -0,0 +1,410 @@
+use super::{
+    database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+    database_error::DatabaseError,
+    database_field::FieldType,
+    database_insertable::DatabaseInsertable,
+    database_query::{QueryOptions, SortDirection},
+    database_type::DatabaseType,
+};
+
+/// Whether a cursor points to the rows sorted after or before the encoded key.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum CursorDirection {
+    After,
+    Before,
+}
+
+/// Sort key of the row a page starts from, serialized as the opaque token
+/// handed to the caller.
+#[derive(Clone, PartialEq, Debug)]
+struct Cursor {
+    direction: CursorDirection,
+    values: Vec<FieldType>,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        let mut raw = String::from(match self.direction {
+            CursorDirection::After => "a",
+            CursorDirection::Before => "b",
+        });
+        for value in self.values.iter() {
+            let (tag, text) = match value {
+                FieldType::SmallInt(val) => ('h', val.to_string()),
+                FieldType::Int(val) => ('i', val.to_string()),
+                FieldType::BigInt(val) => ('l', val.to_string()),
+                FieldType::UnsignedBigInt(val) => ('u', val.to_string()),
+                FieldType::HugeInt(val) => ('q', val.to_string()),
+                FieldType::Real(val) => ('f', val.to_string()),
+                FieldType::Double(val) => ('d', val.to_string()),
+                FieldType::String(val) => ('s', val.clone()),
+                FieldType::Bool(val) => ('b', val.to_string()),
+                FieldType::Null(_) => ('n', String::new()),
+            };
+            raw.push_str(&format!("{}{}:{}", tag, text.len(), text));
+        }
+        return raw.bytes().map(|b| format!("{:02x}", b)).collect();
+    }
+
+    /// Parses a token made by [`Self::encode`] for a sort of `key_count` columns.
+    fn decode(token: &str, key_count: usize) -> Result<Self, DatabaseError> {
+        return match Self::parse(token) {
+            Some(cursor) if cursor.values.len() == key_count => Ok(cursor),
+            _ => Err(DatabaseError::InvalidCursor {
+                cursor: token.to_owned(),
+            }),
+        };
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        if !token.len().is_multiple_of(2) {
+            return None;
+        }
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|idx| u8::from_str_radix(token.get(idx..idx + 2)?, 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+        let raw = String::from_utf8(bytes).ok()?;
+        let direction = match raw.get(..1)? {
+            "a" => CursorDirection::After,
+            "b" => CursorDirection::Before,
+            _ => return None,
+        };
+        let mut rest = &raw[1..];
+        let mut values = Vec::new();
+        while !rest.is_empty() {
+            let tag = rest.chars().next()?;
+            let (len, tail) = rest.get(1..)?.split_once(':')?;
+            let len = len.parse::<usize>().ok()?;
+            let text = tail.get(..len)?;
+            values.push(match tag {
+                'h' => FieldType::SmallInt(text.parse().ok()?),
+                'i' => FieldType::Int(text.parse().ok()?),
+                'l' => FieldType::BigInt(text.parse().ok()?),
+                'u' => FieldType::UnsignedBigInt(text.parse().ok()?),
+                'q' => FieldType::HugeInt(text.parse().ok()?),
+                'f' => FieldType::Real(text.parse().ok()?),
+                'd' => FieldType::Double(text.parse().ok()?),
+                's' => FieldType::String(text.to_owned()),
+                'b' => FieldType::Bool(text.parse().ok()?),
+                _ => return None,
+            });
+            rest = &tail[len..];
+        }
+        return Some(Self { direction, values });
+    }
+}
+
+/// Rows read by [`Paginator::fetch`], with the cursors of the pages around it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Page<D> {
+    items: Vec<D>,
+    next: Option<String>,
+    previous: Option<String>,
+}
+
+impl<D> Page<D> {
+    pub fn items(&self) -> &[D] {
+        return &self.items;
+    }
+
+    pub fn into_items(self) -> Vec<D> {
+        return self.items;
+    }
+
+    /// Cursor of the following page, `None` on the last one.
+    pub fn next_cursor(&self) -> Option<String> {
+        return self.next.clone();
+    }
+
+    /// Cursor of the preceding page, `None` on the first one.
+    pub fn previous_cursor(&self) -> Option<String> {
+        return self.previous.clone();
+    }
+}
+
+/// Keyset pagination over the rows matching a condition.
+///
+/// Rows are sorted by the `order_by` columns then by the primary key, and a page
+/// is read from the sort key of the last (or first) row of the page before it
+/// rather than from an offset, so rows inserted meanwhile neither shift nor
+/// repeat the following pages. Sort columns must not be `NULL`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Paginator {
+    conditions: ConditionExpression,
+    order_by: Vec<(String, SortDirection)>,
+    page_size: u64,
+}
+
+impl Paginator {
+    pub fn new(page_size: u64) -> Self {
+        return Self {
+            conditions: ConditionExpression::default(),
+            order_by: Vec::new(),
+            page_size,
+        };
+    }
+
+    pub fn filter(mut self, conditions: ConditionExpression) -> Self {
+        self.conditions = conditions;
+        return self;
+    }
+
+    /// Sorts by `column`, after the columns given before.
+    pub fn order_by(mut self, column: &str, direction: SortDirection) -> Self {
+        self.order_by.push((column.to_owned(), direction));
+        return self;
+    }
+
+    /// Reads the page `cursor` points to, or the first page when it is `None`.
+    pub async fn fetch<D: DatabaseInsertable, DB: DatabaseType>(
+        &self,
+        db: &DB,
+        cursor: Option<&str>,
+    ) -> Result<Page<D>, DatabaseError> {
+        let limit = self.limit()?;
+        let keys = self.sort_keys::<D>()?;
+        let cursor = match cursor {
+            Some(token) => Some(Cursor::decode(token, keys.len())?),
+            None => None,
+        };
+        let backward = matches!(
+            cursor,
+            Some(Cursor {
+                direction: CursorDirection::Before,
+                ..
+            })
+        );
+
+        let mut options = QueryOptions::new().limit(limit);
+        for (column, direction) in keys.iter() {
+            options = match (backward, direction) {
+                (true, SortDirection::Asc) => options.order_by(column, SortDirection::Desc),
+                (true, SortDirection::Desc) => options.order_by(column, SortDirection::Asc),
+                (false, _) => options.order_by(column, *direction),
+            };
+        }
+        let conditions = match &cursor {
+            Some(cursor) => ConditionExpression::and(vec![
+                self.conditions.clone(),
+                Self::keyset_condition(&keys, cursor)?,
+            ]),
+            None => self.conditions.clone(),
+        };
+
+        let mut items = db.get::<D>(&conditions, &options).await?;
+        let has_more = items.len() as u64 > self.page_size;
+        items.truncate(self.page_size as usize);
+        if backward {
+            items.reverse();
+        }
+
+        let mut next = None;
+        let mut previous = None;
+        if let (Some(first), Some(last)) = (items.first(), items.last()) {
+            if has_more || backward {
+                next = Some(Cursor {
+                    direction: CursorDirection::After,
+                    values: Self::row_key(last, &keys)?,
+                });
+            }
+            if (has_more && backward) || (!backward && cursor.is_some()) {
+                previous = Some(Cursor {
+                    direction: CursorDirection::Before,
+                    values: Self::row_key(first, &keys)?,
+                });
+            }
+        }
+        return Ok(Page {
+            items,
+            next: next.map(|c| c.encode()),
+            previous: previous.map(|c| c.encode()),
+        });
+    }
+
+    /// Rows read for a page, one more than its size telling whether another
+    /// page follows. SQL limits are signed 64 bits.
+    fn limit(&self) -> Result<u64, DatabaseError> {
+        return match self.page_size.checked_add(1) {
+            Some(limit) if self.page_size > 0 && limit <= i64::MAX as u64 => Ok(limit),
+            _ => Err(DatabaseError::InvalidPageSize {
+                page_size: self.page_size,
+            }),
+        };
+    }
+
+    /// Sort columns followed by the primary key ones, which make the order total.
+    fn sort_keys<D: DatabaseInsertable>(
+        &self,
+    ) -> Result<Vec<(String, SortDirection)>, DatabaseError> {
+        let primary_keys = D::default()
+            .fields_value()
+            .into_iter()
+            .filter(|f| f.is_primary_key())
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>();
+        if primary_keys.is_empty() {
+            return Err(DatabaseError::MissingPrimaryKey {
+                table: D::database_name(),
+            });
+        }
+        let mut keys = self.order_by.clone();
+        let direction = match keys.last() {
+            Some((_, direction)) => *direction,
+            None => SortDirection::Asc,
+        };
+        for key in primary_keys {
+            if !keys.iter().any(|(column, _)| *column == key) {
+                keys.push((key, direction));
+            }
+        }
+        return Ok(keys);
+    }
+
+    fn row_key<D: DatabaseInsertable>(
+        row: &D,
+        keys: &[(String, SortDirection)],
+    ) -> Result<Vec<FieldType>, DatabaseError> {
+        let fields = row.fields_value();
+        let mut values = Vec::new();
+        for (column, _) in keys.iter() {
+            match fields.iter().find(|f| f.field_name() == *column) {
+                Some(field) => match field.field_type() {
+                    FieldType::Null(_) => {
+                        return Err(DatabaseError::Decode {
+                            field: Some(column.clone()),
+                            source: "cannot paginate on a NULL sort key".into(),
+                        })
+                    }
+                    value => values.push(value),
+                },
+                None => {
+                    return Err(DatabaseError::UnknownColumn {
+                        column: column.clone(),
+                    })
+                }
+            }
+        }
+        return Ok(values);
+    }
+
+    /// Matches the rows sorted strictly after (or before) the cursor key, that is
+    /// `(a > x) OR (a = x AND b > y) OR ...` for the sort keys `a, b, ...`.
+    fn keyset_condition(
+        keys: &[(String, SortDirection)],
+        cursor: &Cursor,
+    ) -> Result<ConditionExpression, DatabaseError> {
+        let mut branches = Vec::new();
+        for (idx, (column, direction)) in keys.iter().enumerate() {
+            let mut conditions = keys[..idx]
+                .iter()
+                .zip(cursor.values.iter())
+                .map(|((column, _), value)| {
+                    DatabaseCondition::with_value(column, ConditionOperator::Eq, value.clone())
+                })
+                .collect::<Result<Vec<DatabaseCondition>, DatabaseError>>()?;
+            let operator = match (direction, cursor.direction) {
+                (SortDirection::Asc, CursorDirection::After)
+                | (SortDirection::Desc, CursorDirection::Before) => ConditionOperator::Gt,
+                _ => ConditionOperator::Lt,
+            };
+            conditions.push(DatabaseCondition::with_value(
+                column,
+                operator,
+                cursor.values[idx].clone(),
+            )?);
+            branches.push(ConditionExpression::and(conditions));
+        }
+        return Ok(ConditionExpression::or(branches));
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::{
+        database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+        database_error::DatabaseError,
+        database_field::FieldType,
+        database_pagination::{Cursor, CursorDirection, Paginator},
+        database_query::SortDirection,
+    };
+
+    #[test]
+    pub fn cursor_round_trip() {
+        let cursor = Cursor {
+            direction: CursorDirection::Before,
+            values: vec![
+                FieldType::SmallInt(-3),
+                FieldType::UnsignedBigInt(u64::MAX),
+                FieldType::Real(0.1),
+                FieldType::Bool(true),
+                FieldType::from("a:1é"),
+                FieldType::from(""),
+            ],
+        };
+        let token = cursor.encode();
+        assert!(token.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(Cursor::decode(&token, 6).unwrap(), cursor);
+    }
+
+    #[test]
+    pub fn invalid_cursor() {
+        let token = Cursor {
+            direction: CursorDirection::After,
+            values: vec![FieldType::Int(1)],
+        }
+        .encode();
+        assert!(matches!(
+            Cursor::decode(&token, 2),
+            Err(DatabaseError::InvalidCursor { .. })
+        ));
+        for token in ["", "zz", "6", &token[..token.len() - 2]] {
+            assert!(matches!(
+                Cursor::decode(token, 1),
+                Err(DatabaseError::InvalidCursor { cursor }) if cursor == token
+            ));
+        }
+    }
+
+    #[test]
+    pub fn page_size() {
+        assert_eq!(Paginator::new(20).limit().unwrap(), 21);
+        assert_eq!(
+            Paginator::new(i64::MAX as u64 - 1).limit().unwrap(),
+            i64::MAX as u64
+        );
+        for page_size in [0, i64::MAX as u64, u64::MAX] {
+            assert!(matches!(
+                Paginator::new(page_size).limit(),
+                Err(DatabaseError::InvalidPageSize { page_size: size }) if size == page_size
+            ));
+        }
+    }
+
+    #[test]
+    pub fn keyset_condition() {
+        let keys = vec![
+            ("age".to_owned(), SortDirection::Desc),
+            ("id".to_owned(), SortDirection::Asc),
+        ];
+        let cursor = Cursor {
+            direction: CursorDirection::After,
+            values: vec![FieldType::from(30), FieldType::from("b")],
+        };
+        assert_eq!(
+            Paginator::keyset_condition(&keys, &cursor).unwrap(),
+            ConditionExpression::or(vec![
+                ConditionExpression::and(vec![DatabaseCondition::new(
+                    "age",
+                    ConditionOperator::Lt,
+                    30
+                )
+                .unwrap()]),
+                ConditionExpression::and(vec![
+                    DatabaseCondition::new("age", ConditionOperator::Eq, 30).unwrap(),
+                    DatabaseCondition::new("id", ConditionOperator::Gt, "b").unwrap(),
+                ]),
+            ])
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use super::{
+    database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+    database_error::DatabaseError,
+    database_field::FieldType,
+    database_insertable::DatabaseInsertable,
+};
+
+/// Value of the primary key of a row, with one value per primary key field in
+/// the order of `fields_value`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PrimaryKey {
+    values: Vec<FieldType>,
+}
+
+impl PrimaryKey {
+    /// Composite key, also built from a tuple of two or three values.
+    pub fn new(values: Vec<FieldType>) -> Self {
+        return Self { values };
+    }
+
+    /// Key of `data`, read from its primary key fields.
+    pub fn of<D: DatabaseInsertable>(data: &D) -> Result<Self, DatabaseError> {
+        let values = data
+            .fields_value()
+            .into_iter()
+            .filter(|f| f.is_primary_key())
+            .map(|f| f.field_type())
+            .collect::<Vec<FieldType>>();
+        if values.is_empty() {
+            return Err(DatabaseError::MissingPrimaryKey {
+                table: D::database_name(),
+            });
+        }
+        return Ok(Self { values });
+    }
+
+    pub fn values(&self) -> Vec<FieldType> {
+        return self.values.clone();
+    }
+
+    /// Condition matching the row of `D` with this key.
+    pub fn condition<D: DatabaseInsertable>(&self) -> Result<ConditionExpression, DatabaseError> {
+        let columns = D::default()
+            .fields_value()
+            .into_iter()
+            .filter(|f| f.is_primary_key())
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>();
+        if columns.is_empty() {
+            return Err(DatabaseError::MissingPrimaryKey {
+                table: D::database_name(),
+            });
+        }
+        if columns.len() != self.values.len() {
+            return Err(DatabaseError::PrimaryKeyMismatch {
+                table: D::database_name(),
+                expected: columns.len(),
+                found: self.values.len(),
+            });
+        }
+        let conditions = columns
+            .iter()
+            .zip(self.values.iter())
+            .map(|(column, value)| {
+                DatabaseCondition::with_value(column, ConditionOperator::Eq, value.clone())
+            })
+            .collect::<Result<Vec<DatabaseCondition>, DatabaseError>>()?;
+        return Ok(ConditionExpression::and(conditions));
+    }
+}
+
+impl<V> From<V> for PrimaryKey
+where
+    FieldType: From<V>,
+{
+    fn from(val: V) -> Self {
+        return Self::new(vec![FieldType::from(val)]);
+    }
+}
+impl<A, B> From<(A, B)> for PrimaryKey
+where
+    FieldType: From<A> + From<B>,
+{
+    fn from(val: (A, B)) -> Self {
+        return Self::new(vec![FieldType::from(val.0), FieldType::from(val.1)]);
+    }
+}
+impl<A, B, C> From<(A, B, C)> for PrimaryKey
+where
+    FieldType: From<A> + From<B> + From<C>,
+{
+    fn from(val: (A, B, C)) -> Self {
+        return Self::new(vec![
+            FieldType::from(val.0),
+            FieldType::from(val.1),
+            FieldType::from(val.2),
+        ]);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use simple_orm_derive::DatabaseInsertable;
+
+    use crate::models::{
+        database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+        database_error::DatabaseError,
+        database_field::FieldType,
+        database_primary_key::PrimaryKey,
+    };
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Membership {
+        #[simple_orm(primary_key)]
+        group_id: i32,
+        #[simple_orm(primary_key)]
+        user_id: String,
+        role: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Log {
+        message: String,
+    }
+
+    #[test]
+    pub fn of() {
+        let membership = Membership {
+            group_id: 1,
+            user_id: "me".to_owned(),
+            role: "admin".to_owned(),
+        };
+        assert_eq!(
+            PrimaryKey::of(&membership).unwrap(),
+            PrimaryKey::from((1, "me"))
+        );
+        assert!(matches!(
+            PrimaryKey::of(&Log::default()),
+            Err(DatabaseError::MissingPrimaryKey { table }) if table == "log"
+        ));
+    }
+
+    #[test]
+    pub fn condition() {
+        assert_eq!(
+            PrimaryKey::from((1, "me"))
+                .condition::<Membership>()
+                .unwrap(),
+            ConditionExpression::and(vec![
+                DatabaseCondition::new("group_id", ConditionOperator::Eq, 1).unwrap(),
+                DatabaseCondition::new("user_id", ConditionOperator::Eq, "me").unwrap(),
+            ])
+        );
+        assert!(matches!(
+            PrimaryKey::from(1).condition::<Membership>(),
+            Err(DatabaseError::PrimaryKeyMismatch {
+                expected: 2,
+                found: 1,
+                ..
+            })
+        ));
+        assert_eq!(PrimaryKey::from(1).values(), vec![FieldType::Int(1)]);
+    }
+}
@@ -0,0 +1,299 @@
+use super::{
+    database_aggregate::AggregateExpression, database_condition::ConditionExpression,
+    database_error::DatabaseError, database_field::DatabaseField,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+/// Position of `NULL` values in a sort, the database default being `Last` for
+/// `Asc` and `First` for `Desc` in Postgres.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SortOrder {
+    column: String,
+    direction: SortDirection,
+    nulls: Option<NullsOrder>,
+}
+
+impl SortOrder {
+    pub fn column(&self) -> String {
+        return self.column.clone();
+    }
+
+    pub fn direction(&self) -> SortDirection {
+        return self.direction;
+    }
+
+    pub fn nulls(&self) -> Option<NullsOrder> {
+        return self.nulls;
+    }
+}
+
+/// Ordering and pagination of the rows returned by `get`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct QueryOptions {
+    order_by: Vec<SortOrder>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+}
+
+impl QueryOptions {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Sorts by `column`, after the columns given before.
+    pub fn order_by(mut self, column: &str, direction: SortDirection) -> Self {
+        self.order_by.push(SortOrder {
+            column: column.to_owned(),
+            direction,
+            nulls: None,
+        });
+        return self;
+    }
+
+    pub fn order_by_nulls(
+        mut self,
+        column: &str,
+        direction: SortDirection,
+        nulls: NullsOrder,
+    ) -> Self {
+        self.order_by.push(SortOrder {
+            column: column.to_owned(),
+            direction,
+            nulls: Some(nulls),
+        });
+        return self;
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        return self;
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        return self;
+    }
+
+    pub fn sort_orders(&self) -> Vec<SortOrder> {
+        return self.order_by.clone();
+    }
+
+    pub fn limit_value(&self) -> Option<u64> {
+        return self.limit;
+    }
+
+    pub fn offset_value(&self) -> Option<u64> {
+        return self.offset;
+    }
+
+    /// Checks every sort column is one of `fields`, as they are written as is in
+    /// the request.
+    pub fn validate(&self, fields: &[DatabaseField]) -> Result<(), DatabaseError> {
+        for order in self.order_by.iter() {
+            if !fields.iter().any(|f| f.field_name() == order.column) {
+                return Err(DatabaseError::UnknownColumn {
+                    column: order.column.clone(),
+                });
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// `GROUP BY` query, each group being read into a
+/// [`DatabaseResult`](super::database_result::DatabaseResult) whose fields are
+/// group columns or aggregate aliases.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct GroupedQuery {
+    group_by: Vec<String>,
+    aggregates: Vec<AggregateExpression>,
+    conditions: ConditionExpression,
+    having: ConditionExpression,
+    options: QueryOptions,
+}
+
+impl GroupedQuery {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn group_by(mut self, column: &str) -> Self {
+        self.group_by.push(column.to_owned());
+        return self;
+    }
+
+    pub fn aggregate(mut self, aggregate: AggregateExpression) -> Self {
+        self.aggregates.push(aggregate);
+        return self;
+    }
+
+    /// Filters the rows before they are grouped.
+    pub fn filter(mut self, conditions: ConditionExpression) -> Self {
+        self.conditions = conditions;
+        return self;
+    }
+
+    /// Filters the groups, on group columns or aggregate aliases.
+    pub fn having(mut self, conditions: ConditionExpression) -> Self {
+        self.having = conditions;
+        return self;
+    }
+
+    /// Sorts and paginates the groups, on fields of the result.
+    pub fn options(mut self, options: QueryOptions) -> Self {
+        self.options = options;
+        return self;
+    }
+
+    pub fn group_columns(&self) -> Vec<String> {
+        return self.group_by.clone();
+    }
+
+    pub fn aggregates(&self) -> Vec<AggregateExpression> {
+        return self.aggregates.clone();
+    }
+
+    pub fn conditions(&self) -> ConditionExpression {
+        return self.conditions.clone();
+    }
+
+    pub fn having_conditions(&self) -> ConditionExpression {
+        return self.having.clone();
+    }
+
+    pub fn query_options(&self) -> QueryOptions {
+        return self.options.clone();
+    }
+
+    /// Aggregate read into the `alias` field of the result.
+    pub fn aggregate_for(&self, alias: &str) -> Option<AggregateExpression> {
+        return self.aggregates.iter().find(|a| a.alias() == alias).cloned();
+    }
+
+    /// Checks the query against the `fields` of the table and the
+    /// `result_fields` it is read into, every name being written as is in the
+    /// request.
+    pub fn validate(
+        &self,
+        fields: &[DatabaseField],
+        result_fields: &[DatabaseField],
+    ) -> Result<(), DatabaseError> {
+        let unknown = |column: &str| DatabaseError::UnknownColumn {
+            column: column.to_owned(),
+        };
+        for column in self.group_by.iter() {
+            if !fields.iter().any(|f| f.field_name() == *column) {
+                return Err(unknown(column));
+            }
+        }
+        for aggregate in self.aggregates.iter() {
+            aggregate.result_type(fields)?;
+        }
+        let is_output = |column: &str| {
+            self.group_by.iter().any(|c| c == column) || self.aggregate_for(column).is_some()
+        };
+        for field in result_fields.iter() {
+            if !is_output(&field.field_name()) {
+                return Err(unknown(&field.field_name()));
+            }
+        }
+        for column in self.having.columns() {
+            if !is_output(&column) {
+                return Err(unknown(&column));
+            }
+        }
+        return self.options.validate(result_fields);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::{
+        database_aggregate::{AggregateExpression, AggregateFunction},
+        database_condition::{ConditionExpression, ConditionOperator, DatabaseCondition},
+        database_error::DatabaseError,
+        database_field::{DatabaseField, FieldType},
+        database_query::{GroupedQuery, NullsOrder, QueryOptions, SortDirection},
+    };
+
+    #[test]
+    pub fn builder() {
+        let options = QueryOptions::new()
+            .order_by("age", SortDirection::Desc)
+            .order_by_nulls("name", SortDirection::Asc, NullsOrder::First)
+            .limit(10)
+            .offset(20);
+        let orders = options.sort_orders();
+        assert_eq!(orders.len(), 2);
+        assert_eq!(orders[0].column(), "age");
+        assert_eq!(orders[0].direction(), SortDirection::Desc);
+        assert_eq!(orders[0].nulls(), None);
+        assert_eq!(orders[1].nulls(), Some(NullsOrder::First));
+        assert_eq!(options.limit_value(), Some(10));
+        assert_eq!(options.offset_value(), Some(20));
+    }
+
+    #[test]
+    pub fn validate() {
+        let fields = vec![DatabaseField::new("age", FieldType::from(0))];
+        let options = QueryOptions::new().order_by("age", SortDirection::Asc);
+        assert!(options.validate(&fields).is_ok());
+        let options = options.order_by("age; DROP TABLE users", SortDirection::Asc);
+        assert!(matches!(
+            options.validate(&fields),
+            Err(DatabaseError::UnknownColumn { column }) if column == "age; DROP TABLE users"
+        ));
+    }
+
+    #[test]
+    pub fn validate_grouped() {
+        let fields = vec![
+            DatabaseField::new("status", FieldType::from("")),
+            DatabaseField::new("age", FieldType::from(0)),
+        ];
+        let result_fields = vec![
+            DatabaseField::new("status", FieldType::from("")),
+            DatabaseField::new("total", FieldType::BigInt(0)),
+        ];
+        let query = GroupedQuery::new()
+            .group_by("status")
+            .aggregate(AggregateExpression::count_all("total"))
+            .having(ConditionExpression::from(
+                DatabaseCondition::new("total", ConditionOperator::Gt, 1).unwrap(),
+            ))
+            .options(QueryOptions::new().order_by("total", SortDirection::Desc));
+        assert!(query.validate(&fields, &result_fields).is_ok());
+
+        let unknown = |query: GroupedQuery| match query.validate(&fields, &result_fields) {
+            Err(DatabaseError::UnknownColumn { column }) => column,
+            res => panic!("unexpected {:?}", res.map(|_| ())),
+        };
+        assert_eq!(unknown(query.clone().group_by("name")), "name");
+        assert_eq!(
+            unknown(query.clone().aggregate(AggregateExpression::new(
+                AggregateFunction::Max,
+                "name",
+                "oldest"
+            ))),
+            "name"
+        );
+        assert_eq!(
+            unknown(query.clone().having(ConditionExpression::from(
+                DatabaseCondition::new("age", ConditionOperator::Gt, 1).unwrap()
+            ))),
+            "age"
+        );
+        assert_eq!(unknown(GroupedQuery::new().group_by("status")), "total");
+    }
+}
@@ -0,0 +1,63 @@
+use super::database_field::DatabaseField;
+
+/// Row read from a query rather than stored in a table of its own, like the
+/// groups of a [`GroupedQuery`](super::database_query::GroupedQuery).
+pub trait DatabaseResult: Send {
+    /// Columns read, with a value of the type each one is decoded as.
+    fn result_fields() -> Vec<DatabaseField>
+    where
+        Self: Sized;
+
+    fn from_fields(fields: Vec<DatabaseField>) -> Result<Self, String>
+    where
+        Self: Sized;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::models::{
+        database_field::{DatabaseField, FieldType},
+        database_result::DatabaseResult,
+    };
+    use simple_orm_derive::DatabaseResult;
+
+    #[derive(Debug, Default, PartialEq, DatabaseResult)]
+    pub struct StatusCount {
+        status: Option<String>,
+        #[simple_orm(column = "total")]
+        count: i64,
+        #[simple_orm(skip)]
+        label: String,
+    }
+
+    #[test]
+    pub fn result_fields() {
+        let fields = StatusCount::result_fields();
+        let names = fields
+            .iter()
+            .map(|f| f.field_name())
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["status", "total"]);
+        assert_eq!(
+            fields[0].field_type(),
+            FieldType::Null(Box::new(FieldType::from("")))
+        );
+        assert_eq!(fields[1].field_type(), FieldType::BigInt(0));
+    }
+
+    #[test]
+    pub fn from_fields() {
+        let fields = vec![
+            DatabaseField::new("status", FieldType::from("draft")),
+            DatabaseField::new("total", FieldType::BigInt(3)),
+        ];
+        assert_eq!(
+            StatusCount::from_fields(fields).unwrap(),
+            StatusCount {
+                status: Some("draft".to_owned()),
+                count: 3,
+                label: String::new(),
+            }
+        );
+    }
+}
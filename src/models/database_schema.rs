@@ -0,0 +1,396 @@
+use super::{database_field::DatabaseField, database_insertable::DatabaseInsertable};
+
+/// Kind of a table constraint, as far as the fields of a model can describe it.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConstraintKind {
+    PrimaryKey,
+    Unique,
+    /// References the column of a table, given in this order.
+    ForeignKey(String, String),
+}
+
+impl ConstraintKind {
+    /// Rank in which the constraints are added, they are dropped the other way
+    /// around so that a foreign key never outlives the key it references.
+    fn rank(&self) -> u8 {
+        return match self {
+            Self::PrimaryKey => 0,
+            Self::Unique => 1,
+            Self::ForeignKey(_, _) => 2,
+        };
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TableConstraint {
+    name: Option<String>,
+    kind: ConstraintKind,
+    columns: Vec<String>,
+}
+
+impl TableConstraint {
+    pub fn new(kind: ConstraintKind, columns: Vec<String>) -> Self {
+        return Self {
+            name: None,
+            kind,
+            columns,
+        };
+    }
+
+    /// Name given by the database, only known for the constraints read from it.
+    pub fn named(mut self, name: &str) -> Self {
+        self.name = Some(name.to_owned());
+        return self;
+    }
+
+    pub fn name(&self) -> Option<String> {
+        return self.name.clone();
+    }
+
+    pub fn kind(&self) -> ConstraintKind {
+        return self.kind.clone();
+    }
+
+    pub fn columns(&self) -> Vec<String> {
+        return self.columns.clone();
+    }
+
+    /// Whether both constrain the same columns the same way, whatever their
+    /// names.
+    fn same_as(&self, other: &TableConstraint) -> bool {
+        return self.kind == other.kind && self.columns == other.columns;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColumnSchema {
+    name: String,
+    sql_type: String,
+    mandatory: bool,
+}
+
+impl ColumnSchema {
+    pub fn new(name: &str, sql_type: &str, mandatory: bool) -> Self {
+        return Self {
+            name: name.to_owned(),
+            sql_type: sql_type.to_owned(),
+            mandatory,
+        };
+    }
+
+    pub fn name(&self) -> String {
+        return self.name.clone();
+    }
+
+    pub fn sql_type(&self) -> String {
+        return self.sql_type.clone();
+    }
+
+    pub fn is_mandatory(&self) -> bool {
+        return self.mandatory;
+    }
+}
+
+/// Columns and constraints of a table, read from the database or expected by
+/// a model.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct TableSchema {
+    columns: Vec<ColumnSchema>,
+    constraints: Vec<TableConstraint>,
+}
+
+impl TableSchema {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Schema expected by `D`, `column` describing each field as the backend
+    /// declares it.
+    pub fn of<D: DatabaseInsertable>(column: impl Fn(&DatabaseField) -> ColumnSchema) -> Self {
+        let mut schema = Self::new();
+        let mut primary_key = Vec::new();
+        for field in D::default().fields_value().iter() {
+            schema = schema.column(column(field));
+            if field.is_primary_key() {
+                primary_key.push(field.field_name());
+            }
+            if field.unique() {
+                schema = schema.constraint(TableConstraint::new(
+                    ConstraintKind::Unique,
+                    vec![field.field_name()],
+                ));
+            }
+            // Reserved names are quoted in SQL, not in the catalog.
+            if let Some((table, foreign_column)) = field.is_foreign_key() {
+                schema = schema.constraint(TableConstraint::new(
+                    ConstraintKind::ForeignKey(
+                        table.replace('"', ""),
+                        foreign_column.replace('"', ""),
+                    ),
+                    vec![field.field_name()],
+                ));
+            }
+        }
+        if !primary_key.is_empty() {
+            schema = schema.constraint(TableConstraint::new(
+                ConstraintKind::PrimaryKey,
+                primary_key,
+            ));
+        }
+        return schema;
+    }
+
+    pub fn column(mut self, column: ColumnSchema) -> Self {
+        self.columns.push(column);
+        return self;
+    }
+
+    pub fn constraint(mut self, constraint: TableConstraint) -> Self {
+        self.constraints.push(constraint);
+        return self;
+    }
+
+    pub fn columns(&self) -> Vec<ColumnSchema> {
+        return self.columns.clone();
+    }
+
+    pub fn constraints(&self) -> Vec<TableConstraint> {
+        return self.constraints.clone();
+    }
+
+    fn find_column(&self, name: &str) -> Option<&ColumnSchema> {
+        return self.columns.iter().find(|c| c.name == name);
+    }
+
+    /// Changes turning this schema into `target`, in the order they can be
+    /// run: constraints are dropped before the columns they cover and added
+    /// once every column is in place.
+    pub fn changes_to(&self, target: &TableSchema) -> Vec<SchemaChange> {
+        let mut dropped_constraints = self
+            .constraints
+            .iter()
+            .filter(|c| !target.constraints.iter().any(|t| t.same_as(c)))
+            .cloned()
+            .collect::<Vec<TableConstraint>>();
+        dropped_constraints.sort_by_key(|c| std::cmp::Reverse(c.kind.rank()));
+        let mut added_constraints = target
+            .constraints
+            .iter()
+            .filter(|t| !self.constraints.iter().any(|c| c.same_as(t)))
+            .cloned()
+            .collect::<Vec<TableConstraint>>();
+        added_constraints.sort_by_key(|c| c.kind.rank());
+
+        let mut changes = dropped_constraints
+            .into_iter()
+            .map(SchemaChange::DropConstraint)
+            .collect::<Vec<SchemaChange>>();
+        for column in self.columns.iter() {
+            if target.find_column(&column.name).is_none() {
+                changes.push(SchemaChange::DropColumn(column.name()));
+            }
+        }
+        let mut added_columns = Vec::new();
+        let mut nullability = Vec::new();
+        for column in target.columns.iter() {
+            let Some(current) = self.find_column(&column.name) else {
+                added_columns.push(SchemaChange::AddColumn(column.clone()));
+                continue;
+            };
+            if !current.sql_type.eq_ignore_ascii_case(&column.sql_type) {
+                changes.push(SchemaChange::AlterColumnType {
+                    column: column.name(),
+                    from: current.sql_type(),
+                    to: column.sql_type(),
+                });
+            }
+            match (current.mandatory, column.mandatory) {
+                (false, true) => nullability.push(SchemaChange::SetNotNull(column.name())),
+                (true, false) => nullability.push(SchemaChange::DropNotNull(column.name())),
+                _ => {}
+            }
+        }
+        changes.extend(added_columns);
+        changes.extend(nullability);
+        changes.extend(
+            added_constraints
+                .into_iter()
+                .map(SchemaChange::AddConstraint),
+        );
+        return changes;
+    }
+}
+
+/// Change of a table found by
+/// [`DatabaseType::diff_schema`](super::database_type::DatabaseType::diff_schema).
+#[derive(Clone, PartialEq, Debug)]
+pub enum SchemaChange {
+    /// The table does not exist yet.
+    CreateTable,
+    AddColumn(ColumnSchema),
+    DropColumn(String),
+    AlterColumnType {
+        column: String,
+        from: String,
+        to: String,
+    },
+    SetNotNull(String),
+    DropNotNull(String),
+    AddConstraint(TableConstraint),
+    DropConstraint(TableConstraint),
+    /// The other changes cannot be made in place, so the table is copied into a
+    /// new one replacing it, which loses its indexes and triggers.
+    RebuildTable,
+}
+
+impl SchemaChange {
+    /// Whether applying the change can lose data or what guards it: dropping a
+    /// column or a constraint, converting values or rebuilding the table.
+    pub fn is_destructive(&self) -> bool {
+        return matches!(
+            self,
+            Self::DropColumn(_)
+                | Self::AlterColumnType { .. }
+                | Self::DropConstraint(_)
+                | Self::RebuildTable
+        );
+    }
+}
+
+/// Changes bringing a table in line with its model, and the statements
+/// making them.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SchemaPlan {
+    changes: Vec<SchemaChange>,
+    statements: Vec<String>,
+}
+
+impl SchemaPlan {
+    pub fn new(changes: Vec<SchemaChange>, statements: Vec<String>) -> Self {
+        return Self {
+            changes,
+            statements,
+        };
+    }
+
+    pub fn changes(&self) -> Vec<SchemaChange> {
+        return self.changes.clone();
+    }
+
+    pub fn statements(&self) -> Vec<String> {
+        return self.statements.clone();
+    }
+
+    /// Whether the table already matches the model.
+    pub fn is_empty(&self) -> bool {
+        return self.changes.is_empty();
+    }
+
+    pub fn is_destructive(&self) -> bool {
+        return self.changes.iter().any(|c| c.is_destructive());
+    }
+
+    pub fn destructive_changes(&self) -> Vec<SchemaChange> {
+        return self
+            .changes
+            .iter()
+            .filter(|c| c.is_destructive())
+            .cloned()
+            .collect();
+    }
+
+    /// Statements of the plan, one per line.
+    pub fn sql(&self) -> String {
+        return self.statements.join("\n");
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use simple_orm_derive::DatabaseInsertable;
+
+    use crate::models::database_schema::{
+        ColumnSchema, ConstraintKind, SchemaChange, SchemaPlan, TableConstraint, TableSchema,
+    };
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Article {
+        #[simple_orm(primary_key)]
+        id: i64,
+        #[simple_orm(unique)]
+        slug: String,
+        #[simple_orm(foreign_key(authors, id))]
+        author: Option<String>,
+    }
+
+    fn expected() -> TableSchema {
+        return TableSchema::of::<Article>(|field| {
+            ColumnSchema::new(&field.field_name(), "TEXT", field.is_mandatory())
+        });
+    }
+
+    #[test]
+    pub fn of_model() {
+        let schema = expected();
+        assert_eq!(
+            schema.columns(),
+            vec![
+                ColumnSchema::new("id", "TEXT", true),
+                ColumnSchema::new("slug", "TEXT", true),
+                ColumnSchema::new("author", "TEXT", false),
+            ]
+        );
+        assert_eq!(
+            schema.constraints(),
+            vec![
+                TableConstraint::new(ConstraintKind::Unique, vec!["slug".to_owned()]),
+                TableConstraint::new(
+                    ConstraintKind::ForeignKey("authors".to_owned(), "id".to_owned()),
+                    vec!["author".to_owned()]
+                ),
+                TableConstraint::new(ConstraintKind::PrimaryKey, vec!["id".to_owned()]),
+            ]
+        );
+        assert!(expected().changes_to(&schema).is_empty());
+    }
+
+    #[test]
+    pub fn ordered_changes() {
+        let unique_legacy =
+            TableConstraint::new(ConstraintKind::Unique, vec!["legacy".to_owned()]).named("uq");
+        let primary_key =
+            TableConstraint::new(ConstraintKind::PrimaryKey, vec!["id".to_owned()]).named("pk");
+        let live = TableSchema::new()
+            .column(ColumnSchema::new("id", "text", true))
+            .column(ColumnSchema::new("slug", "INTEGER", false))
+            .column(ColumnSchema::new("legacy", "TEXT", true))
+            .constraint(unique_legacy.clone())
+            .constraint(primary_key);
+        let changes = live.changes_to(&expected());
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::DropConstraint(unique_legacy),
+                SchemaChange::DropColumn("legacy".to_owned()),
+                SchemaChange::AlterColumnType {
+                    column: "slug".to_owned(),
+                    from: "INTEGER".to_owned(),
+                    to: "TEXT".to_owned(),
+                },
+                SchemaChange::AddColumn(ColumnSchema::new("author", "TEXT", false)),
+                SchemaChange::SetNotNull("slug".to_owned()),
+                SchemaChange::AddConstraint(TableConstraint::new(
+                    ConstraintKind::Unique,
+                    vec!["slug".to_owned()]
+                )),
+                SchemaChange::AddConstraint(TableConstraint::new(
+                    ConstraintKind::ForeignKey("authors".to_owned(), "id".to_owned()),
+                    vec!["author".to_owned()]
+                )),
+            ]
+        );
+        let plan = SchemaPlan::new(changes, Vec::new());
+        assert!(plan.is_destructive());
+        assert_eq!(plan.destructive_changes().len(), 3);
+    }
+}
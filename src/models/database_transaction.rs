@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+
+use super::{database_error::DatabaseError, database_type::DatabaseType};
+
+/// Isolation level of a transaction, see the database documentation for the
+/// anomalies each one prevents.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+/// Database on which transactions can be started.
+///
+/// The transaction borrows the database until it is finished, so every
+/// operation made meanwhile goes through it.
+#[async_trait]
+pub trait DatabaseTransactional: DatabaseType {
+    type Transaction<'a>: DatabaseTransaction
+    where
+        Self: 'a;
+
+    /// Starts a transaction with the default isolation level of the database.
+    async fn begin<'a>(&'a mut self) -> Result<Self::Transaction<'a>, DatabaseError>;
+    async fn begin_with_isolation<'a>(
+        &'a mut self,
+        isolation_level: IsolationLevel,
+    ) -> Result<Self::Transaction<'a>, DatabaseError>;
+}
+
+/// Transaction started by [`DatabaseTransactional::begin`].
+///
+/// Operations are only visible to other connections once it is committed, the
+/// transaction being rolled back if it is dropped before.
+#[async_trait]
+pub trait DatabaseTransaction: DatabaseType + Sized {
+    async fn commit(self) -> Result<(), DatabaseError>;
+    async fn rollback(self) -> Result<(), DatabaseError>;
+}
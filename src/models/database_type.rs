@@ -1,25 +1,165 @@
 use async_trait::async_trait;
 
 use super::{
-    database_condition::DatabaseCondition, database_error::DatabaseError,
+    database_aggregate::AggregateFunction,
+    database_condition::ConditionExpression,
+    database_error::DatabaseError,
+    database_field::FieldType,
     database_insertable::DatabaseInsertable,
+    database_primary_key::PrimaryKey,
+    database_query::{GroupedQuery, QueryOptions},
+    database_result::DatabaseResult,
+    database_schema::SchemaPlan,
+    database_upsert::{UpsertOptions, UpsertOutcome},
 };
 
 #[async_trait]
-pub trait DatabaseType: Send {
-    async fn initialize<D: DatabaseInsertable>(&mut self) -> Result<(), DatabaseError>;
-    async fn insert<D: DatabaseInsertable>(&mut self, data: D) -> Result<(), DatabaseError>;
-    async fn update<D: DatabaseInsertable>(
-        &mut self,
+pub trait DatabaseType: Send + Sync {
+    async fn initialize<D: DatabaseInsertable>(&self) -> Result<(), DatabaseError>;
+    async fn insert<D: DatabaseInsertable>(&self, data: D) -> Result<(), DatabaseError>;
+    /// Inserts all of `data` or none of it, with as few statements as the bind
+    /// parameter limit of the database allows, and returns the number of rows
+    /// inserted.
+    async fn insert_many<D: DatabaseInsertable>(&self, data: Vec<D>) -> Result<u64, DatabaseError>;
+    /// Inserts `data`, or resolves the conflict as told by `options` when its key
+    /// is already taken, in a single statement.
+    async fn upsert<D: DatabaseInsertable>(
+        &self,
         data: D,
-        conditions: &[DatabaseCondition],
-    ) -> Result<(), DatabaseError>;
-    async fn delete<D: DatabaseInsertable>(
-        &mut self,
-        conditions: &[DatabaseCondition],
-    ) -> Result<(), DatabaseError>;
+        options: &UpsertOptions,
+    ) -> Result<UpsertOutcome, DatabaseError>;
+    /// Inserts `data` and returns the row as saved, with the values filled by
+    /// the database.
+    async fn insert_returning<D: DatabaseInsertable>(&self, data: D) -> Result<D, DatabaseError>;
+    /// Updates the matching rows and returns how many there were.
+    async fn update_affected<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError>;
+    /// Updates the matching rows and returns them as saved.
+    async fn update_returning<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError>;
+    /// Deletes the matching rows and returns how many there were.
+    async fn delete_affected<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError>;
+    /// Deletes the matching rows and returns them as they were.
+    async fn delete_returning<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<Vec<D>, DatabaseError>;
     async fn get<D: DatabaseInsertable>(
-        &mut self,
-        conditions: &[DatabaseCondition],
+        &self,
+        conditions: &ConditionExpression,
+        options: &QueryOptions,
     ) -> Result<Vec<D>, DatabaseError>;
+    async fn count<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<u64, DatabaseError>;
+    async fn exists<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<bool, DatabaseError>;
+    /// Computes `function` over `column` for the matching rows, the value being
+    /// a [`FieldType::Null`] when no row matches.
+    async fn aggregate<D: DatabaseInsertable>(
+        &self,
+        function: AggregateFunction,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError>;
+    /// Runs `query` over the rows of `D`, reading each group as an `R`.
+    async fn get_grouped<D: DatabaseInsertable, R: DatabaseResult>(
+        &self,
+        query: &GroupedQuery,
+    ) -> Result<Vec<R>, DatabaseError>;
+    /// Compares the table of `D` in the database with the fields of `D` and
+    /// plans the changes bringing it in line, creating it when missing.
+    async fn diff_schema<D: DatabaseInsertable>(&self) -> Result<SchemaPlan, DatabaseError>;
+    /// Runs the statements of `plan`, all of them or none.
+    async fn apply_schema(&self, plan: &SchemaPlan) -> Result<(), DatabaseError>;
+
+    async fn update<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+        conditions: &ConditionExpression,
+    ) -> Result<(), DatabaseError> {
+        self.update_affected(data, conditions).await?;
+        return Ok(());
+    }
+    async fn delete<D: DatabaseInsertable>(
+        &self,
+        conditions: &ConditionExpression,
+    ) -> Result<(), DatabaseError> {
+        self.delete_affected::<D>(conditions).await?;
+        return Ok(());
+    }
+
+    async fn sum<D: DatabaseInsertable>(
+        &self,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        return self
+            .aggregate::<D>(AggregateFunction::Sum, column, conditions)
+            .await;
+    }
+    async fn avg<D: DatabaseInsertable>(
+        &self,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        return self
+            .aggregate::<D>(AggregateFunction::Avg, column, conditions)
+            .await;
+    }
+    async fn min<D: DatabaseInsertable>(
+        &self,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        return self
+            .aggregate::<D>(AggregateFunction::Min, column, conditions)
+            .await;
+    }
+    async fn max<D: DatabaseInsertable>(
+        &self,
+        column: &str,
+        conditions: &ConditionExpression,
+    ) -> Result<FieldType, DatabaseError> {
+        return self
+            .aggregate::<D>(AggregateFunction::Max, column, conditions)
+            .await;
+    }
+
+    /// Row of `D` with the primary key `key`, a tuple for composite keys.
+    async fn find<D: DatabaseInsertable, K: Into<PrimaryKey> + Send>(
+        &self,
+        key: K,
+    ) -> Result<Option<D>, DatabaseError> {
+        let conditions = key.into().condition::<D>()?;
+        let rows = self
+            .get::<D>(&conditions, &QueryOptions::new().limit(1))
+            .await?;
+        return Ok(rows.into_iter().next());
+    }
+    /// Updates the row with the primary key of `data`.
+    async fn save<D: DatabaseInsertable + Sync>(&self, data: &D) -> Result<(), DatabaseError> {
+        let conditions = PrimaryKey::of(data)?.condition::<D>()?;
+        return self.update(data, &conditions).await;
+    }
+    /// Deletes the row with the primary key of `data`.
+    async fn delete_entity<D: DatabaseInsertable + Sync>(
+        &self,
+        data: &D,
+    ) -> Result<(), DatabaseError> {
+        let conditions = PrimaryKey::of(data)?.condition::<D>()?;
+        return self.delete::<D>(&conditions).await;
+    }
 }
@@ -0,0 +1,216 @@
+use super::{
+    database_error::DatabaseError, database_field::DatabaseField,
+    database_insertable::DatabaseInsertable,
+};
+
+/// What an upsert does with the existing row when the key is already taken.
+#[derive(Clone, PartialEq, Debug)]
+pub enum ConflictAction {
+    /// Keeps the existing row as is.
+    DoNothing,
+    /// Overwrites every column but the conflict target.
+    UpdateAll,
+    /// Overwrites the given columns only.
+    Update(Vec<String>),
+}
+
+/// How [`DatabaseType::upsert`](super::database_type::DatabaseType::upsert)
+/// resolves a conflict, by default updating every column when the primary key
+/// already exists.
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpsertOptions {
+    target: Option<Vec<String>>,
+    action: ConflictAction,
+}
+
+impl Default for UpsertOptions {
+    fn default() -> Self {
+        return Self {
+            target: None,
+            action: ConflictAction::UpdateAll,
+        };
+    }
+}
+
+impl UpsertOptions {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    /// Conflicts on the unique `columns` rather than the primary key, which must
+    /// match a unique constraint or index.
+    pub fn on_conflict(mut self, columns: &[&str]) -> Self {
+        self.target = Some(columns.iter().map(|c| c.to_string()).collect());
+        return self;
+    }
+
+    pub fn do_nothing(mut self) -> Self {
+        self.action = ConflictAction::DoNothing;
+        return self;
+    }
+
+    pub fn update_columns(mut self, columns: &[&str]) -> Self {
+        self.action = ConflictAction::Update(columns.iter().map(|c| c.to_string()).collect());
+        return self;
+    }
+
+    pub fn action(&self) -> ConflictAction {
+        return self.action.clone();
+    }
+
+    /// Columns of the conflict target in `D`, the primary key ones by default.
+    pub fn conflict_columns<D: DatabaseInsertable>(&self) -> Result<Vec<String>, DatabaseError> {
+        let fields = D::default().fields_value();
+        let columns = match &self.target {
+            Some(columns) => columns.clone(),
+            None => fields
+                .iter()
+                .filter(|f| f.is_primary_key())
+                .map(|f| f.field_name())
+                .collect(),
+        };
+        if columns.is_empty() {
+            return Err(DatabaseError::MissingPrimaryKey {
+                table: D::database_name(),
+            });
+        }
+        Self::validate(&columns, &fields)?;
+        return Ok(columns);
+    }
+
+    /// Columns of `D` overwritten on conflict, empty when the row is left as is.
+    /// `UpdateAll` leaves out the columns generated by the database and the
+    /// conflict target, which `Update` rejects.
+    pub fn updated_columns<D: DatabaseInsertable>(&self) -> Result<Vec<String>, DatabaseError> {
+        let fields = D::default().fields_value();
+        return match &self.action {
+            ConflictAction::DoNothing => Ok(Vec::new()),
+            ConflictAction::UpdateAll => {
+                let target = self.conflict_columns::<D>()?;
+                Ok(fields
+                    .iter()
+                    .filter(|f| !f.is_auto_increment())
+                    .map(|f| f.field_name())
+                    .filter(|name| !target.contains(name))
+                    .collect())
+            }
+            ConflictAction::Update(columns) => {
+                Self::validate(columns, &fields)?;
+                let target = self.conflict_columns::<D>()?;
+                for column in columns.iter() {
+                    let generated = fields
+                        .iter()
+                        .any(|f| f.field_name() == *column && f.is_auto_increment());
+                    if generated || target.contains(column) {
+                        return Err(DatabaseError::UnwritableColumn {
+                            column: column.clone(),
+                        });
+                    }
+                }
+                Ok(columns.clone())
+            }
+        };
+    }
+
+    fn validate(columns: &[String], fields: &[DatabaseField]) -> Result<(), DatabaseError> {
+        for column in columns.iter() {
+            if !fields.iter().any(|f| f.field_name() == *column) {
+                return Err(DatabaseError::UnknownColumn {
+                    column: column.clone(),
+                });
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// What an upsert did.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UpsertOutcome {
+    Inserted,
+    Updated,
+    /// The key existed and the row was left as is.
+    Unchanged,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use simple_orm_derive::DatabaseInsertable;
+
+    use crate::models::{database_error::DatabaseError, database_upsert::UpsertOptions};
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Account {
+        #[simple_orm(primary_key)]
+        id: i32,
+        #[simple_orm(unique)]
+        email: String,
+        name: String,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Event {
+        #[simple_orm(primary_key, auto_increment)]
+        id: i64,
+        #[simple_orm(unique)]
+        name: String,
+        count: i32,
+    }
+
+    #[derive(Default, DatabaseInsertable)]
+    pub struct Log {
+        message: String,
+    }
+
+    #[test]
+    pub fn default_target() {
+        let options = UpsertOptions::new();
+        assert_eq!(options.conflict_columns::<Account>().unwrap(), vec!["id"]);
+        assert_eq!(
+            options.updated_columns::<Account>().unwrap(),
+            vec!["email", "name"]
+        );
+        assert!(options
+            .do_nothing()
+            .updated_columns::<Account>()
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            UpsertOptions::new().conflict_columns::<Log>(),
+            Err(DatabaseError::MissingPrimaryKey { table }) if table == "log"
+        ));
+    }
+
+    #[test]
+    pub fn chosen_columns() {
+        let options = UpsertOptions::new()
+            .on_conflict(&["email"])
+            .update_columns(&["name"]);
+        assert_eq!(
+            options.conflict_columns::<Account>().unwrap(),
+            vec!["email"]
+        );
+        assert_eq!(options.updated_columns::<Account>().unwrap(), vec!["name"]);
+        let options = options.update_columns(&["missing"]);
+        assert!(matches!(
+            options.updated_columns::<Account>(),
+            Err(DatabaseError::UnknownColumn { column }) if column == "missing"
+        ));
+    }
+
+    #[test]
+    pub fn unwritable_columns() {
+        let options = UpsertOptions::new().on_conflict(&["name"]);
+        assert_eq!(options.updated_columns::<Event>().unwrap(), vec!["count"]);
+        let options = options.update_columns(&["count", "id"]);
+        assert!(matches!(
+            options.updated_columns::<Event>(),
+            Err(DatabaseError::UnwritableColumn { column }) if column == "id"
+        ));
+        let options = options.update_columns(&["name"]);
+        assert!(matches!(
+            options.updated_columns::<Event>(),
+            Err(DatabaseError::UnwritableColumn { column }) if column == "name"
+        ));
+    }
+}
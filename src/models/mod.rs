@@ -1,5 +1,13 @@
+pub mod database_aggregate;
 pub mod database_condition;
 pub mod database_error;
 pub mod database_field;
 pub mod database_insertable;
+pub mod database_pagination;
+pub mod database_primary_key;
+pub mod database_query;
+pub mod database_result;
+pub mod database_schema;
+pub mod database_transaction;
 pub mod database_type;
+pub mod database_upsert;
@@ -0,0 +1,14 @@
+#[test]
+pub fn derive_in_external_crate() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/external_model.rs");
+}
+
+#[test]
+pub fn invalid_attributes() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/unknown_attribute.rs");
+    t.compile_fail("tests/ui/foreign_key_arity.rs");
+    t.compile_fail("tests/ui/column_not_string.rs");
+    t.compile_fail("tests/ui/skip_primary_key.rs");
+}
@@ -0,0 +1,9 @@
+use simple_orm::DatabaseInsertable;
+
+#[derive(Default, DatabaseInsertable)]
+struct User {
+    #[simple_orm(primary_key, column = 42)]
+    id: String,
+}
+
+fn main() {}
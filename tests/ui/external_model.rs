@@ -0,0 +1,57 @@
+use simple_orm::{
+    models::{database_insertable::DatabaseInsertable, database_result::DatabaseResult},
+    DatabaseInsertable, DatabaseResult,
+};
+
+#[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+#[simple_orm(table = "users")]
+struct User {
+    #[simple_orm(primary_key)]
+    id: String,
+    name: Option<String>,
+    age: u8,
+    activated: bool,
+}
+
+mod reexport {
+    pub use simple_orm as orm;
+}
+
+#[derive(Debug, Default, PartialEq, DatabaseInsertable)]
+#[simple_orm(crate = "reexport::orm")]
+struct Setting {
+    #[simple_orm(primary_key)]
+    key: String,
+    value: f64,
+}
+
+#[derive(Debug, Default, PartialEq, DatabaseResult)]
+struct AgeCount {
+    age: u8,
+    total: i64,
+}
+
+fn main() {
+    let user = User {
+        id: "id".to_owned(),
+        name: None,
+        age: 42,
+        activated: true,
+    };
+    assert_eq!(User::database_name(), "users");
+    assert_eq!(User::from_fields(user.fields_value()).unwrap(), user);
+
+    let setting = Setting {
+        key: "ratio".to_owned(),
+        value: 0.5,
+    };
+    assert_eq!(Setting::database_name(), "setting");
+    assert_eq!(Setting::from_fields(setting.fields_value()).unwrap(), setting);
+
+    let fields = AgeCount::result_fields();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(
+        AgeCount::from_fields(fields).unwrap(),
+        AgeCount { age: 0, total: 0 }
+    );
+}
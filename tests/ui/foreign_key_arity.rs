@@ -0,0 +1,11 @@
+use simple_orm::DatabaseInsertable;
+
+#[derive(Default, DatabaseInsertable)]
+struct Order {
+    #[simple_orm(primary_key)]
+    id: String,
+    #[simple_orm(foreign_key(users))]
+    buyer: String,
+}
+
+fn main() {}
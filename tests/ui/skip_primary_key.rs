@@ -0,0 +1,9 @@
+use simple_orm::DatabaseInsertable;
+
+#[derive(Default, DatabaseInsertable)]
+struct User {
+    #[simple_orm(skip, primary_key)]
+    id: String,
+}
+
+fn main() {}